@@ -0,0 +1,299 @@
+//! 平稳跟踪分析：根据目标轨迹与眼动 / 指针采样计算增益、扫视与信号中断。
+//!
+//! 所有函数只依赖采样序列，既可在训练中实时调用，也可离线分析录制的训练记录。
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// 带时间戳（关卡时间，秒）的位置采样，使用逻辑坐标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimedSample {
+    pub t: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// 在按时间排序的采样上线性插值；早于第一个采样时为 `None`，晚于最后一个时取最后一个
+pub fn interpolate(samples: &[TimedSample], t: f64) -> Option<(f64, f64)> {
+    let first = samples.first()?;
+    if t < first.t {
+        return None;
+    }
+    let index = samples.partition_point(|p| p.t <= t);
+    if index >= samples.len() {
+        let last = samples.last()?;
+        return Some((last.x, last.y));
+    }
+    let (a, b) = (&samples[index - 1], &samples[index]);
+    let span = b.t - a.t;
+    let k = if span > 0.0 { (t - a.t) / span } else { 0.0 };
+    Some((a.x + (b.x - a.x) * k, a.y + (b.y - a.y) * k))
+}
+
+/// 分析阈值，速度单位为逻辑像素 / 秒
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisParams {
+    /// 眼速超过目标速度这么多时视为扫视
+    pub saccade_speed: f64,
+    /// 目标速度低于此值的区间不参与增益计算
+    pub min_target_speed: f64,
+    /// 相邻采样间隔超过此值（秒）视为信号中断
+    pub max_sample_gap: f64,
+    /// 不超过此时长（秒）的中断视为眨眼，更长的视为丢失信号
+    pub max_blink: f64,
+}
+
+impl AnalysisParams {
+    /// 按屏幕高度换算的默认阈值：扫视 2 屏高 / 秒，最低目标速度 0.05 屏高 / 秒
+    pub fn for_screen_height(screen_h: f64) -> Self {
+        AnalysisParams {
+            saccade_speed: 2.0 * screen_h,
+            min_target_speed: 0.05 * screen_h,
+            max_sample_gap: 0.1,
+            max_blink: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Saccade {
+    pub start: f64,
+    pub end: f64,
+    /// 起止点之间的距离
+    pub amplitude: f64,
+    pub peak_speed: f64,
+    /// 是否缩小了与目标的距离（追赶性扫视）
+    pub catch_up: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DropoutKind {
+    Blink,
+    SignalLoss,
+}
+
+/// 采样中断（眨眼或眼动仪丢失信号）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Dropout {
+    pub start: f64,
+    pub end: f64,
+    pub kind: DropoutKind,
+}
+
+/// 一组采样（眼动或指针）相对目标的跟踪分析结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PursuitReport {
+    pub samples: u32,
+    /// 平稳跟踪增益：眼速在目标速度方向上的投影与目标速度之比，1.0 为理想
+    pub gain: Option<f64>,
+    pub catch_up_saccades: u32,
+    pub saccades: Vec<Saccade>,
+    pub blinks: u32,
+    pub dropouts: Vec<Dropout>,
+    /// 中断的累计时长（秒）
+    pub dropout_time: f64,
+    /// 与目标的均方根距离（逻辑像素）
+    pub rms_error: f64,
+}
+
+/// 单个关卡的分析报告
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageReport {
+    pub stage: i32,
+    pub duration: f64,
+    pub pointer: Option<PursuitReport>,
+    pub gaze: Option<PursuitReport>,
+}
+
+impl StageReport {
+    /// 根据目标轨迹与指针 / 眼动采样生成报告，三者的时间均为关卡时间
+    pub fn analyze(
+        stage: i32,
+        target: &[TimedSample],
+        pointer: &[TimedSample],
+        gaze: &[TimedSample],
+        params: &AnalysisParams,
+    ) -> Self {
+        StageReport {
+            stage,
+            duration: target.last().map_or(0.0, |p| p.t),
+            pointer: analyze_pursuit(target, pointer, params),
+            gaze: analyze_pursuit(target, gaze, params),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// 分析一组采样跟随目标的情况；没有落在目标轨迹时间范围内的采样时为 `None`
+pub fn analyze_pursuit(target: &[TimedSample], samples: &[TimedSample], params: &AnalysisParams) -> Option<PursuitReport> {
+    let mut points: Vec<TimedSample> = samples
+        .iter()
+        .copied()
+        .filter(|p| interpolate(target, p.t).is_some())
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    points.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+    let error_at = |p: &TimedSample| interpolate(target, p.t).map_or(0.0, |(tx, ty)| (p.x - tx).hypot(p.y - ty));
+    let squared_sum: f64 = points.iter().map(|p| error_at(p).powi(2)).sum();
+
+    let mut report = PursuitReport {
+        samples: points.len() as u32,
+        rms_error: (squared_sum / points.len() as f64).sqrt(),
+        ..PursuitReport::default()
+    };
+
+    // 当前扫视的起点索引与峰值速度
+    let mut saccade: Option<(usize, f64)> = None;
+    let (mut projected, mut target_energy) = (0.0, 0.0);
+    for i in 1..points.len() {
+        let (a, b) = (&points[i - 1], &points[i]);
+        let dt = b.t - a.t;
+        if dt > params.max_sample_gap {
+            if let Some((start, peak)) = saccade.take() {
+                report.saccades.push(close_saccade(&points, start, i - 1, peak, &error_at));
+            }
+            let kind = if dt <= params.max_blink { DropoutKind::Blink } else { DropoutKind::SignalLoss };
+            report.dropouts.push(Dropout { start: a.t, end: b.t, kind });
+            continue;
+        }
+        if dt <= 0.0 {
+            continue;
+        }
+
+        let (ta, tb) = (interpolate(target, a.t), interpolate(target, b.t));
+        let (Some(ta), Some(tb)) = (ta, tb) else {
+            continue;
+        };
+        let (ex, ey) = ((b.x - a.x) / dt, (b.y - a.y) / dt);
+        let (tx, ty) = ((tb.0 - ta.0) / dt, (tb.1 - ta.1) / dt);
+        let eye_speed = ex.hypot(ey);
+        let target_speed = tx.hypot(ty);
+
+        if eye_speed > target_speed + params.saccade_speed {
+            let (_, peak) = saccade.get_or_insert((i - 1, 0.0));
+            *peak = peak.max(eye_speed);
+            continue;
+        }
+        if let Some((start, peak)) = saccade.take() {
+            report.saccades.push(close_saccade(&points, start, i - 1, peak, &error_at));
+        }
+
+        if target_speed > params.min_target_speed {
+            projected += ex * tx + ey * ty;
+            target_energy += tx * tx + ty * ty;
+        }
+    }
+    if let Some((start, peak)) = saccade {
+        report.saccades.push(close_saccade(&points, start, points.len() - 1, peak, &error_at));
+    }
+
+    report.gain = (target_energy > 0.0).then(|| projected / target_energy);
+    report.catch_up_saccades = report.saccades.iter().filter(|s| s.catch_up).count() as u32;
+    report.blinks = report.dropouts.iter().filter(|d| d.kind == DropoutKind::Blink).count() as u32;
+    report.dropout_time = report.dropouts.iter().map(|d| d.end - d.start).sum();
+    Some(report)
+}
+
+fn close_saccade(
+    points: &[TimedSample],
+    start: usize,
+    end: usize,
+    peak_speed: f64,
+    error_at: &impl Fn(&TimedSample) -> f64,
+) -> Saccade {
+    let (a, b) = (&points[start], &points[end]);
+    Saccade {
+        start: a.t,
+        end: b.t,
+        amplitude: (b.x - a.x).hypot(b.y - a.y),
+        peak_speed,
+        catch_up: error_at(b) < error_at(a),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 目标以 540 px/s 水平运动 10 秒
+    fn target() -> Vec<TimedSample> {
+        (0..=2400)
+            .map(|i| {
+                let t = i as f64 / 240.0;
+                TimedSample { t, x: 100.0 + 540.0 * t, y: 540.0 }
+            })
+            .collect()
+    }
+
+    /// 眼睛以 `gain` 倍速度跟随，每 `saccade_every` 个采样跳回目标，120 Hz 采样
+    fn pursue(gain: f64, saccade_every: Option<usize>) -> Vec<TimedSample> {
+        let mut x = 100.0;
+        (0..=1200)
+            .map(|i| {
+                let t = i as f64 / 120.0;
+                if i > 0 {
+                    x += 540.0 * gain / 120.0;
+                }
+                if saccade_every.is_some_and(|n| i > 0 && i % n == 0) {
+                    x = 100.0 + 540.0 * t;
+                }
+                TimedSample { t, x, y: 540.0 }
+            })
+            .collect()
+    }
+
+    fn analyze(samples: &[TimedSample]) -> PursuitReport {
+        analyze_pursuit(&target(), samples, &AnalysisParams::for_screen_height(1080.0)).unwrap()
+    }
+
+    #[test]
+    fn test_perfect_pursuit() {
+        let report = analyze(&pursue(1.0, None));
+        assert_eq!(report.samples, 1201);
+        assert!((report.gain.unwrap() - 1.0).abs() < 1e-6);
+        assert!(report.saccades.is_empty());
+        assert!(report.dropouts.is_empty());
+        assert!(report.rms_error < 1e-6);
+    }
+
+    #[test]
+    fn test_low_gain_with_catch_up_saccades() {
+        let report = analyze(&pursue(0.6, Some(60)));
+        assert!((report.gain.unwrap() - 0.6).abs() < 1e-6);
+        assert_eq!(report.catch_up_saccades, 20);
+        assert!(report.saccades.iter().all(|s| s.amplitude > 100.0));
+        assert!(report.rms_error > 50.0);
+    }
+
+    #[test]
+    fn test_blinks_and_signal_loss() {
+        let samples: Vec<TimedSample> = pursue(1.0, None)
+            .into_iter()
+            .filter(|p| !(2.0..2.2).contains(&p.t) && !(5.0..6.0).contains(&p.t))
+            .collect();
+        let report = analyze(&samples);
+        assert_eq!(report.blinks, 1);
+        assert_eq!(report.dropouts.len(), 2);
+        assert_eq!(report.dropouts[1].kind, DropoutKind::SignalLoss);
+        assert!((report.dropout_time - 1.2 - 2.0 / 120.0).abs() < 1e-6);
+        // 中断前后的位置差不应被当作扫视
+        assert!(report.saccades.is_empty());
+    }
+
+    #[test]
+    fn test_stage_report_json() {
+        let report = StageReport::analyze(2, &target(), &pursue(1.0, None), &[], &AnalysisParams::for_screen_height(1080.0));
+        assert_eq!(report.duration, 10.0);
+        assert!(report.gaze.is_none());
+
+        let json = report.to_json().unwrap();
+        let restored: StageReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, report);
+    }
+}
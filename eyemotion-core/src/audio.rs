@@ -0,0 +1,475 @@
+//! 程序化音频合成：把 [`BGMParams`] 与 [`SFXParams`] 渲染成单声道 PCM 采样，
+//! 各平台得到完全相同的声音，也可以直接检查采样做单元测试。
+//!
+//! 编曲与前端 `audio.ts` 一致：每拍 0.45 秒，每 8 拍换一个和弦，
+//! 底鼓、低音、大提琴、吉他、钢琴与两条旋律线分别使用不同的波形和包络。
+//! 噪声踩镲、滤波与颤音没有移植。
+//!
+//! 渲染结果可以写成 WAV 文件（16 位整数或 32 位浮点），不需要音频设备。
+
+use crate::{BGMParams, CoreError, Result, SFXParams};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+/// 导出 WAV 时允许的采样率范围
+pub const MIN_SAMPLE_RATE: u32 = 8_000;
+pub const MAX_SAMPLE_RATE: u32 = 384_000;
+/// 背景音乐每拍的时长（秒）
+pub const BEAT_DURATION: f64 = 0.45;
+/// 每个和弦持续的拍数
+pub const BEATS_PER_CHORD: usize = 8;
+/// 指数衰减的终点，与 Web Audio 的 `exponentialRampToValueAtTime` 目标值相同
+const SILENCE: f64 = 0.0001;
+/// 碰撞音效在音量为 1 时的峰值
+const BOUNCE_GAIN: f64 = 0.25;
+
+/// 单声道 PCM 采样，取值范围 -1..=1
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcmBuffer {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl PcmBuffer {
+    pub fn silent(sample_rate: u32, duration: f64) -> Self {
+        let len = (duration.max(0.0) * sample_rate as f64).round() as usize;
+        PcmBuffer { sample_rate, samples: vec![0.0; len] }
+    }
+
+    /// 时长（秒）
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+
+    pub fn peak(&self) -> f32 {
+        self.samples.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    pub fn rms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self.samples.iter().map(|s| (*s as f64).powi(2)).sum();
+        (sum / self.samples.len() as f64).sqrt() as f32
+    }
+}
+
+/// WAV 文件的采样格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WavFormat {
+    Pcm16,
+    Float32,
+}
+
+impl PcmBuffer {
+    /// 写成单声道 WAV；浮点格式按规范带 `fact` 块
+    pub fn write_wav<W: Write>(&self, format: WavFormat, mut out: W) -> Result<()> {
+        let (tag, bytes, fmt_size): (u16, u32, u32) = match format {
+            WavFormat::Pcm16 => (1, 2, 16),
+            WavFormat::Float32 => (3, 4, 18),
+        };
+        let data_size = u32::try_from(self.samples.len())
+            .ok()
+            .and_then(|n| n.checked_mul(bytes))
+            .filter(|n| *n <= u32::MAX - 64)
+            .ok_or_else(|| CoreError::Config("audio: too many samples for a WAV file".to_string()))?;
+        let byte_rate = self
+            .sample_rate
+            .checked_mul(bytes)
+            .ok_or_else(|| CoreError::Config(format!("audio: sample rate {} is too high", self.sample_rate)))?;
+        let fact_size = if format == WavFormat::Float32 { 12 } else { 0 };
+
+        out.write_all(b"RIFF")?;
+        out.write_all(&(4 + 8 + fmt_size + fact_size + 8 + data_size + data_size % 2).to_le_bytes())?;
+        out.write_all(b"WAVEfmt ")?;
+        out.write_all(&fmt_size.to_le_bytes())?;
+        out.write_all(&tag.to_le_bytes())?;
+        out.write_all(&1u16.to_le_bytes())?;
+        out.write_all(&self.sample_rate.to_le_bytes())?;
+        out.write_all(&byte_rate.to_le_bytes())?;
+        out.write_all(&(bytes as u16).to_le_bytes())?;
+        out.write_all(&(bytes as u16 * 8).to_le_bytes())?;
+        if format == WavFormat::Float32 {
+            out.write_all(&0u16.to_le_bytes())?;
+            out.write_all(b"fact")?;
+            out.write_all(&4u32.to_le_bytes())?;
+            out.write_all(&(self.samples.len() as u32).to_le_bytes())?;
+        }
+        out.write_all(b"data")?;
+        out.write_all(&data_size.to_le_bytes())?;
+        for sample in &self.samples {
+            let sample = sample.clamp(-1.0, 1.0);
+            match format {
+                WavFormat::Pcm16 => out.write_all(&((sample * i16::MAX as f32).round() as i16).to_le_bytes())?,
+                WavFormat::Float32 => out.write_all(&sample.to_le_bytes())?,
+            }
+        }
+        if data_size % 2 == 1 {
+            out.write_all(&[0])?;
+        }
+        Ok(())
+    }
+
+    pub fn save_wav(&self, path: &Path, format: WavFormat) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_wav(format, &mut out)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Triangle,
+    Square,
+    Sawtooth,
+}
+
+impl Waveform {
+    /// `phase` 为周期的小数部分（0..1）
+    pub fn sample(self, phase: f64) -> f64 {
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Triangle => 4.0 * ((phase - 0.25).rem_euclid(1.0) - 0.5).abs() - 1.0,
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+        }
+    }
+}
+
+/// 音符包络：线性起音到峰值，线性衰减到 `sustain`，之后指数衰减到音符结束
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f64,
+    pub decay: f64,
+    pub sustain: f64,
+}
+
+impl Envelope {
+    /// 只有指数衰减的打击型包络
+    pub const PERCUSSIVE: Envelope = Envelope { attack: 0.0, decay: 0.0, sustain: 1.0 };
+
+    /// 音符开始 `t` 秒后的增益（0..=1），`duration` 为音符时长
+    pub fn level(&self, t: f64, duration: f64) -> f64 {
+        if t < 0.0 || t >= duration {
+            return 0.0;
+        }
+        if t < self.attack {
+            return t / self.attack;
+        }
+        let held = self.attack + self.decay;
+        if t < held {
+            return 1.0 - (1.0 - self.sustain) * (t - self.attack) / self.decay;
+        }
+        if self.sustain <= SILENCE || held >= duration {
+            return self.sustain;
+        }
+        self.sustain * (SILENCE / self.sustain).powf((t - held) / (duration - held))
+    }
+}
+
+/// 一个音符；频率从 `freq` 指数滑到 `freq_end`（与 Web Audio 的频率斜坡相同）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Note {
+    pub start: f64,
+    pub duration: f64,
+    pub freq: f64,
+    pub freq_end: f64,
+    pub gain: f64,
+    pub waveform: Waveform,
+    pub envelope: Envelope,
+}
+
+impl Note {
+    fn frequency_at(&self, t: f64) -> f64 {
+        if self.freq_end == self.freq || self.duration <= 0.0 {
+            return self.freq;
+        }
+        self.freq * (self.freq_end / self.freq).powf((t / self.duration).min(1.0))
+    }
+
+    /// 叠加到 `buffer`；`wrap` 为真时超出末尾的部分回绕到开头，用于无缝循环
+    pub fn mix_into(&self, buffer: &mut PcmBuffer, wrap: bool) {
+        let rate = buffer.sample_rate as f64;
+        let len = buffer.samples.len();
+        if len == 0 || !(self.freq > 0.0 && self.freq_end > 0.0) {
+            return;
+        }
+        let first = (self.start * rate).round() as usize;
+        let count = (self.duration * rate).round() as usize;
+        let mut phase = 0.0;
+        for i in 0..count {
+            let index = first + i;
+            let index = if wrap { index % len } else if index < len { index } else { break };
+            let t = i as f64 / rate;
+            let value = self.waveform.sample(phase) * self.gain * self.envelope.level(t, self.duration);
+            buffer.samples[index] += value as f32;
+            phase = (phase + self.frequency_at(t) / rate).fract();
+        }
+    }
+}
+
+/// 碰撞音效：正弦波从 `bounce_freq_start` 滑到 `bounce_freq_end`，音量指数衰减。
+/// 不检查 `enabled`，是否播放由调用方决定
+pub fn render_bounce(sfx: &SFXParams, sample_rate: u32) -> PcmBuffer {
+    let mut buffer = PcmBuffer::silent(sample_rate, sfx.bounce_duration);
+    Note {
+        start: 0.0,
+        duration: sfx.bounce_duration,
+        freq: sfx.bounce_freq_start,
+        freq_end: sfx.bounce_freq_end,
+        gain: BOUNCE_GAIN * sfx.volume as f64,
+        waveform: Waveform::Sine,
+        envelope: Envelope::PERCUSSIVE,
+    }
+    .mix_into(&mut buffer, false);
+    buffer
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// 和弦与旋律都回到开头所需的拍数
+pub fn bgm_loop_beats(bgm: &BGMParams) -> usize {
+    let chords = BEATS_PER_CHORD * bgm.chords.len().max(1);
+    let melody = bgm.melody.len().max(1);
+    chords / gcd(chords, melody) * melody
+}
+
+/// 和弦音数不固定，按位置循环取音
+fn chord_note(chord: &[f64], index: usize) -> f64 {
+    chord[index % chord.len()]
+}
+
+/// 第 `beat` 拍开始的音符
+fn beat_notes(bgm: &BGMParams, beat: usize) -> Vec<Note> {
+    let Some(chord) = bgm.chords.get(beat / BEATS_PER_CHORD % bgm.chords.len().max(1)) else {
+        return Vec::new();
+    };
+    if chord.is_empty() {
+        return Vec::new();
+    }
+    let start = beat as f64 * BEAT_DURATION;
+    let note = |freq: f64, gain: f64, beats: f64, waveform: Waveform, envelope: Envelope| Note {
+        start,
+        duration: BEAT_DURATION * beats,
+        freq,
+        freq_end: freq,
+        gain,
+        waveform,
+        envelope,
+    };
+
+    let mut notes = Vec::new();
+    if beat.is_multiple_of(2) {
+        // 底鼓：正弦波快速下滑
+        notes.push(Note { duration: 0.2, freq_end: 0.01, ..note(120.0, 0.4, 1.0, Waveform::Sine, Envelope::PERCUSSIVE) });
+        // 低音
+        let envelope = Envelope { attack: 0.05, decay: 0.1, sustain: 0.5 };
+        notes.push(note(chord[0] * 0.5, 0.12, 1.0, Waveform::Triangle, envelope));
+    }
+    if beat.is_multiple_of(BEATS_PER_CHORD) {
+        // 大提琴长音
+        let envelope = Envelope { attack: 1.5, decay: 0.5, sustain: 0.8 };
+        notes.push(note(chord[0] * 0.5, 0.1, 8.5, Waveform::Sawtooth, envelope));
+    }
+    // 吉他分解和弦
+    let envelope = Envelope { attack: 0.1, decay: 0.5, sustain: 0.4 };
+    notes.push(note(chord_note(chord, beat % 4), 0.04, 2.0, Waveform::Square, envelope));
+    // 钢琴旋律，0 为休止
+    if let Some(&freq) = bgm.melody.get(beat % bgm.melody.len().max(1)) {
+        if freq > 0.0 {
+            let envelope = Envelope { attack: 0.01, decay: 0.3, sustain: 0.4 };
+            notes.push(note(freq, 0.12, 1.5, Waveform::Triangle, envelope));
+        }
+    }
+    // 旋律线 A 与 B
+    if beat.is_multiple_of(4) {
+        let envelope = Envelope { attack: 0.2, decay: 0.3, sustain: 0.7 };
+        notes.push(note(chord_note(chord, 2) * 2.0, 0.08, 6.0, Waveform::Triangle, envelope));
+    }
+    if (beat + 2).is_multiple_of(BEATS_PER_CHORD) {
+        let envelope = Envelope { attack: 0.3, decay: 0.4, sustain: 0.6 };
+        notes.push(note(chord_note(chord, 1) * 2.0, 0.05, 7.0, Waveform::Square, envelope));
+    }
+    notes
+}
+
+/// 渲染 `beats` 拍背景音乐，乘以 `volume`；结尾的余音回绕到开头，
+/// 因此 `beats` 为 [`bgm_loop_beats`] 的倍数时可以无缝循环播放
+pub fn render_bgm(bgm: &BGMParams, beats: usize, sample_rate: u32) -> PcmBuffer {
+    mix_bgm(bgm, beats, beats as f64 * BEAT_DURATION, true, sample_rate)
+}
+
+/// 渲染 `duration` 秒背景音乐，从第一拍开始播放，到时长处截断
+pub fn render_bgm_for(bgm: &BGMParams, duration: f64, sample_rate: u32) -> PcmBuffer {
+    let duration = duration.max(0.0);
+    let beats = (duration / BEAT_DURATION).ceil() as usize;
+    mix_bgm(bgm, beats, duration, false, sample_rate)
+}
+
+fn mix_bgm(bgm: &BGMParams, beats: usize, duration: f64, wrap: bool, sample_rate: u32) -> PcmBuffer {
+    let mut buffer = PcmBuffer::silent(sample_rate, duration);
+    for beat in 0..beats {
+        for note in beat_notes(bgm, beat) {
+            note.mix_into(&mut buffer, wrap);
+        }
+    }
+    let volume = bgm.volume.clamp(0.0, 1.0);
+    for sample in &mut buffer.samples {
+        *sample = (*sample * volume).clamp(-1.0, 1.0);
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AudioParams;
+
+    /// 通过上升过零点估计 `from..to` 秒之间的频率
+    fn frequency(buffer: &PcmBuffer, from: f64, to: f64) -> f64 {
+        let rate = buffer.sample_rate as f64;
+        let samples = &buffer.samples[(from * rate) as usize..(to * rate) as usize];
+        let crossings = samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        crossings as f64 / (to - from)
+    }
+
+    #[test]
+    fn test_waveforms_and_envelope() {
+        for waveform in [Waveform::Sine, Waveform::Triangle, Waveform::Square, Waveform::Sawtooth] {
+            assert!((0..100).all(|i| waveform.sample(i as f64 / 100.0).abs() <= 1.0));
+        }
+        assert!((Waveform::Triangle.sample(0.25) - 1.0).abs() < 1e-12);
+        assert!((Waveform::Triangle.sample(0.75) + 1.0).abs() < 1e-12);
+
+        let envelope = Envelope { attack: 0.1, decay: 0.1, sustain: 0.5 };
+        assert_eq!(envelope.level(0.05, 1.0), 0.5);
+        assert!((envelope.level(0.1, 1.0) - 1.0).abs() < 1e-12);
+        assert!((envelope.level(0.2, 1.0) - 0.5).abs() < 1e-12);
+        assert!(envelope.level(0.999, 1.0) < 0.001);
+        assert_eq!(envelope.level(1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_bounce_sweep() {
+        let sfx = AudioParams::default().sfx;
+        let buffer = render_bounce(&sfx, DEFAULT_SAMPLE_RATE);
+        assert_eq!(buffer.samples.len(), (0.08 * DEFAULT_SAMPLE_RATE as f64).round() as usize);
+        assert!(buffer.peak() <= BOUNCE_GAIN as f32 * sfx.volume);
+        assert!(buffer.peak() > 0.1);
+
+        // 前半段频率高于后半段，且都在起止频率之间
+        let early = frequency(&buffer, 0.0, 0.04);
+        let late = frequency(&buffer, 0.04, 0.08);
+        assert!(early > late, "{} {}", early, late);
+        assert!(early <= 150.0 && late >= 75.0 - 25.0, "{} {}", early, late);
+
+        let quiet = render_bounce(&SFXParams { volume: 0.0, ..sfx }, DEFAULT_SAMPLE_RATE);
+        assert_eq!(quiet.peak(), 0.0);
+    }
+
+    #[test]
+    fn test_note_pitch() {
+        let mut buffer = PcmBuffer::silent(DEFAULT_SAMPLE_RATE, 1.0);
+        Note {
+            start: 0.0,
+            duration: 1.0,
+            freq: 440.0,
+            freq_end: 440.0,
+            gain: 1.0,
+            waveform: Waveform::Sine,
+            envelope: Envelope { attack: 0.0, decay: 0.0, sustain: 1.0 },
+        }
+        .mix_into(&mut buffer, false);
+        assert!((frequency(&buffer, 0.0, 0.5) - 440.0).abs() <= 2.0);
+    }
+
+    #[test]
+    fn test_bgm_loop() {
+        let bgm = AudioParams::default().bgm;
+        let beats = bgm_loop_beats(&bgm);
+        assert_eq!(beats, 32);
+
+        let buffer = render_bgm(&bgm, beats, 8_000);
+        assert_eq!(buffer.samples.len(), (beats as f64 * BEAT_DURATION * 8_000.0).round() as usize);
+        assert!(buffer.peak() <= 1.0);
+        assert!(buffer.rms() > 0.001);
+        assert_eq!(buffer, render_bgm(&bgm, beats, 8_000));
+
+        let louder = render_bgm(&BGMParams { volume: 0.3, ..bgm.clone() }, beats, 8_000);
+        assert!((louder.rms() / buffer.rms() - 2.0).abs() < 0.01);
+        assert_eq!(render_bgm(&BGMParams { volume: 0.0, ..bgm.clone() }, beats, 8_000).peak(), 0.0);
+
+        // 不回绕时开头没有上一轮的余音，其余部分相同
+        let once = render_bgm_for(&bgm, 3.0, 8_000);
+        assert_eq!(once.samples.len(), 24_000);
+        assert_eq!(once.samples[20_000..], buffer.samples[20_000..24_000]);
+        assert_ne!(once.samples[..100], buffer.samples[..100]);
+    }
+
+    #[test]
+    fn test_wav_layout() {
+        let buffer = PcmBuffer { sample_rate: 8_000, samples: vec![0.0, 0.5, -1.0, 2.0] };
+
+        let mut pcm = Vec::new();
+        buffer.write_wav(WavFormat::Pcm16, &mut pcm).unwrap();
+        assert_eq!(pcm.len(), 44 + 8);
+        assert_eq!(&pcm[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(pcm[4..8].try_into().unwrap()), 44 + 8 - 8);
+        assert_eq!(&pcm[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([pcm[20], pcm[21]]), 1);
+        assert_eq!(u32::from_le_bytes(pcm[24..28].try_into().unwrap()), 8_000);
+        assert_eq!(&pcm[36..40], b"data");
+        let samples: Vec<i16> = pcm[44..].chunks(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+        assert_eq!(samples, [0, 16_384, -32_767, 32_767]);
+
+        let mut float = Vec::new();
+        buffer.write_wav(WavFormat::Float32, &mut float).unwrap();
+        assert_eq!(float.len(), 58 + 16);
+        assert_eq!(u16::from_le_bytes([float[20], float[21]]), 3);
+        assert_eq!(&float[38..42], b"fact");
+        assert_eq!(&float[50..54], b"data");
+        let samples: Vec<f32> = float[58..].chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(samples, [0.0, 0.5, -1.0, 1.0]);
+
+        let too_fast = PcmBuffer { sample_rate: u32::MAX / 2 + 1, samples: vec![0.0] };
+        assert!(matches!(too_fast.write_wav(WavFormat::Pcm16, Vec::new()), Err(CoreError::Config(_))));
+        assert!(too_fast.write_wav(WavFormat::Float32, Vec::new()).is_err());
+    }
+
+    /// 与仓库中的参考文件逐字节比较；设置 `UPDATE_GOLDEN=1` 时重新生成参考文件
+    fn assert_golden(buffer: &PcmBuffer, name: &str) {
+        let mut wav = Vec::new();
+        buffer.write_wav(WavFormat::Pcm16, &mut wav).unwrap();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, &wav).unwrap();
+        }
+        let golden = std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        assert!(wav == golden, "{} differs from the rendered audio", path.display());
+    }
+
+    #[test]
+    fn test_golden_wav() {
+        let audio = AudioParams::default();
+        assert_golden(&render_bounce(&audio.sfx, MIN_SAMPLE_RATE), "bounce.wav");
+        assert_golden(&render_bgm(&audio.bgm, 2, MIN_SAMPLE_RATE), "bgm.wav");
+    }
+}
@@ -1,10 +1,14 @@
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+use crate::{CoreError, Result};
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct AudioParams {
     pub bgm: BGMParams,
     pub sfx: SFXParams,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct BGMParams {
     pub enabled: bool,
     pub volume: f32,
@@ -13,6 +17,7 @@ pub struct BGMParams {
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct SFXParams {
     pub enabled: bool,
     pub volume: f32,
@@ -21,29 +26,91 @@ pub struct SFXParams {
     pub bounce_duration: f64,
 }
 
-impl Default for AudioParams {
+impl AudioParams {
+    /// 检查音量在 0..=1 之间，频率与时长为正数，和弦不为空；旋律中的 0 表示休止
+    pub fn validate(&self) -> Result<()> {
+        let positive = |v: f64| v.is_finite() && v > 0.0;
+        let volume = |v: f32| (0.0..=1.0).contains(&v);
+        let bgm = &self.bgm;
+        let sfx = &self.sfx;
+
+        let invalid = if !volume(bgm.volume) {
+            Some("bgm.volume")
+        } else if bgm.chords.is_empty() || bgm.chords.iter().any(|c| c.is_empty() || !c.iter().all(|f| positive(*f))) {
+            Some("bgm.chords")
+        } else if !bgm.melody.iter().all(|f| *f == 0.0 || positive(*f)) {
+            Some("bgm.melody")
+        } else if !volume(sfx.volume) {
+            Some("sfx.volume")
+        } else if !positive(sfx.bounce_freq_start) {
+            Some("sfx.bounce_freq_start")
+        } else if !positive(sfx.bounce_freq_end) {
+            Some("sfx.bounce_freq_end")
+        } else if !positive(sfx.bounce_duration) {
+            Some("sfx.bounce_duration")
+        } else {
+            None
+        };
+        match invalid {
+            Some(field) => Err(CoreError::Config(format!("audio: field `{}` is invalid", field))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for BGMParams {
+    fn default() -> Self {
+        BGMParams {
+            enabled: true,
+            volume: 0.15,
+            chords: vec![
+                vec![130.81, 261.63, 329.63, 392.00, 493.88],
+                vec![220.00, 261.63, 329.63, 392.00],
+                vec![174.61, 261.63, 329.63, 349.23],
+                vec![196.00, 493.88, 293.66, 349.23],
+            ],
+            melody: vec![
+                261.63, 329.63, 392.00, 493.88, 440.00, 392.00, 329.63, 293.66,
+            ],
+        }
+    }
+}
+
+impl Default for SFXParams {
     fn default() -> Self {
-        AudioParams {
-            bgm: BGMParams {
-                enabled: true,
-                volume: 0.15,
-                chords: vec![
-                    vec![130.81, 261.63, 329.63, 392.00, 493.88],
-                    vec![220.00, 261.63, 329.63, 392.00],
-                    vec![174.61, 261.63, 329.63, 349.23],
-                    vec![196.00, 493.88, 293.66, 349.23],
-                ],
-                melody: vec![
-                    261.63, 329.63, 392.00, 493.88, 440.00, 392.00, 329.63, 293.66,
-                ],
-            },
-            sfx: SFXParams {
-                enabled: true,
-                volume: 0.8,
-                bounce_freq_start: 150.0,
-                bounce_freq_end: 75.0,
-                bounce_duration: 0.08,
-            },
+        SFXParams {
+            enabled: true,
+            volume: 0.8,
+            bounce_freq_start: 150.0,
+            bounce_freq_end: 75.0,
+            bounce_duration: 0.08,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(AudioParams::default().validate().is_ok());
+
+        let mut audio = AudioParams::default();
+        audio.bgm.melody.push(0.0);
+        assert!(audio.validate().is_ok());
+
+        let invalid = |change: fn(&mut AudioParams)| {
+            let mut audio = AudioParams::default();
+            change(&mut audio);
+            audio.validate().unwrap_err().to_string()
+        };
+        assert!(invalid(|a| a.bgm.volume = 1.5).contains("bgm.volume"));
+        assert!(invalid(|a| a.sfx.volume = f32::NAN).contains("sfx.volume"));
+        assert!(invalid(|a| a.bgm.chords.clear()).contains("bgm.chords"));
+        assert!(invalid(|a| a.bgm.chords[1].clear()).contains("bgm.chords"));
+        assert!(invalid(|a| a.bgm.melody[0] = -440.0).contains("bgm.melody"));
+        assert!(invalid(|a| a.sfx.bounce_freq_end = 0.0).contains("sfx.bounce_freq_end"));
+        assert!(invalid(|a| a.sfx.bounce_duration = f64::INFINITY).contains("sfx.bounce_duration"));
+    }
+}
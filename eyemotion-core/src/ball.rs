@@ -1,10 +1,14 @@
 use crate::Result;
+use rand::Rng;
+use std::f64::consts::PI;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Ball {
     pub screen_w: f64,
     pub screen_h: f64,
     pub radius: f64,
+    /// 景深缩放比例，半径 = 基础半径 × scale
+    pub scale: f64,
     pub x: f64,
     pub y: f64,
     pub vx: f64,
@@ -18,6 +22,7 @@ impl Ball {
             screen_h,
             // 基于高度计算半径，保持在不同宽高比下的视觉一致性 (1080 / 22.5 = 48)
             radius: screen_h / 22.5,
+            scale: 1.0,
             x: screen_w / 2.0,
             y: screen_h / 2.0,
             vx: 0.0,
@@ -25,16 +30,22 @@ impl Ball {
         }
     }
 
-    pub fn reset_to_random_pos(&mut self, screen_w: f64, screen_h: f64) {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    pub fn reset_to_random_pos<R: Rng>(&mut self, screen_w: f64, screen_h: f64, rng: &mut R) {
         self.screen_w = screen_w;
         self.screen_h = screen_h;
         self.update_radius();
         let r = self.radius;
-        // 随机生成起始点，避开边缘
-        self.x = rng.gen_range((r + 50.0)..(self.screen_w - r - 50.0));
-        self.y = rng.gen_range((r + 50.0)..(self.screen_h - r - 50.0));
+        // 随机生成起始点，避开边缘；屏幕放不下时居中
+        let mut random = |size: f64| {
+            let (lo, hi) = (r + 50.0, size - r - 50.0);
+            if lo < hi {
+                rng.gen_range(lo..hi)
+            } else {
+                size / 2.0
+            }
+        };
+        self.x = random(self.screen_w);
+        self.y = random(self.screen_h);
         self.vx = 0.0;
         self.vy = 0.0;
     }
@@ -72,7 +83,18 @@ impl Ball {
     }
 
     pub fn update_radius(&mut self) {
-        self.radius = self.screen_h / 22.5;
+        self.radius = self.screen_h / 22.5 * self.scale;
+    }
+
+    /// 修改景深缩放比例；变大后若与边界重叠则推回边界内，
+    /// 之后的碰撞检测会让仍朝外运动的小球立即反弹
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+        self.update_radius();
+
+        let r = self.radius;
+        self.x = self.x.clamp(r, (self.screen_w - r).max(r));
+        self.y = self.y.clamp(r, (self.screen_h - r).max(r));
     }
 
     pub fn set_speed(&mut self, speed: f64, direction: Option<(f64, f64)>) {
@@ -118,38 +140,141 @@ impl Ball {
         self.y = center_y + orbit_radius * angle.sin();
     }
 
-    pub fn update(&mut self, dt: f64) -> Result<bool> {
-        self.x += self.vx * dt;
-        self.y += self.vy * dt;
+    /// 按参数曲线直接定位到关卡时间 `t` 处，速度取曲线导数；
+    /// 振幅按小球半径收缩，保证 `amplitude <= 1` 时不会越过边界
+    pub fn update_lissajous(&mut self, t: f64, freq_x: f64, freq_y: f64, phase: f64, amplitude: f64) {
+        let center_x = self.screen_w / 2.0;
+        let center_y = self.screen_h / 2.0;
+        let ax = amplitude * (center_x - self.radius).max(0.0);
+        let ay = amplitude * (center_y - self.radius).max(0.0);
+        let wx = 2.0 * PI * freq_x;
+        let wy = 2.0 * PI * freq_y;
 
-        let mut bounced = false;
+        self.x = center_x + ax * (wx * t + phase).sin();
+        self.y = center_y + ay * (wy * t).sin();
+        self.vx = ax * wx * (wx * t + phase).cos();
+        self.vy = ay * wy * (wy * t).cos();
+    }
+
+    /// 连续碰撞检测：计算精确的碰撞时刻，反弹后用剩余时间沿新方向继续运动。
+    /// 返回本步内发生的所有反弹（按时间先后），`time` 为相对本步起点的秒数。
+    pub fn update(&mut self, dt: f64) -> Result<Vec<Bounce>> {
+        let mut bounces = Vec::new();
         let r = self.radius;
+        let (min_x, max_x) = (r, self.screen_w - r);
+        let (min_y, max_y) = (r, self.screen_h - r);
 
-        // 边界检测：使用严格的逻辑边界
-        if self.x < r {
-            self.x = r;
-            self.vx = self.vx.abs();
-            bounced = true;
-        } else if self.x > self.screen_w - r {
-            self.x = self.screen_w - r; 
-            self.vx = -self.vx.abs();
-            bounced = true;
-        }
+        let mut elapsed = 0.0;
+        for _ in 0..MAX_BOUNCES_PER_STEP {
+            let remaining = dt - elapsed;
+            let t_x = time_to_wall(self.x, self.vx, min_x, max_x);
+            let t_y = time_to_wall(self.y, self.vy, min_y, max_y);
+            let t_hit = t_x.min(t_y);
+
+            if t_hit > remaining {
+                self.x += self.vx * remaining;
+                self.y += self.vy * remaining;
+                return Ok(bounces);
+            }
+
+            self.x += self.vx * t_hit;
+            self.y += self.vy * t_hit;
+            elapsed += t_hit;
+
+            // 两个轴的碰撞时刻足够接近时视为撞到角落，在同一次反弹中处理
+            let hit_x = t_x - t_hit <= CORNER_EPSILON;
+            let hit_y = t_y - t_hit <= CORNER_EPSILON;
+            let mut horizontal = None;
+            let mut vertical = None;
 
-        if self.y < r {
-            self.y = r;
-            self.vy = self.vy.abs();
-            bounced = true;
-        } else if self.y > self.screen_h - r {
-            self.y = self.screen_h - r;
-            self.vy = -self.vy.abs();
-            bounced = true;
+            if hit_x {
+                if self.vx > 0.0 {
+                    self.x = max_x;
+                    self.vx = -self.vx;
+                    horizontal = Some(Wall::Right);
+                } else {
+                    self.x = min_x;
+                    self.vx = -self.vx;
+                    horizontal = Some(Wall::Left);
+                }
+            }
+            if hit_y {
+                if self.vy > 0.0 {
+                    self.y = max_y;
+                    self.vy = -self.vy;
+                    vertical = Some(Wall::Bottom);
+                } else {
+                    self.y = min_y;
+                    self.vy = -self.vy;
+                    vertical = Some(Wall::Top);
+                }
+            }
+
+            bounces.push(Bounce {
+                x: self.x,
+                y: self.y,
+                time: elapsed,
+                wall: Wall::combine(horizontal, vertical),
+            });
         }
 
-        Ok(bounced)
+        // 反弹次数过多（例如屏幕比小球还小）时退回到直接限制在边界内
+        self.x = self.x.clamp(min_x, max_x.max(min_x));
+        self.y = self.y.clamp(min_y, max_y.max(min_y));
+        Ok(bounces)
+    }
+}
+
+/// 单步内最多处理的反弹次数
+const MAX_BOUNCES_PER_STEP: usize = 16;
+/// 判定为同时撞到两面墙（角落）的时间容差（秒）
+const CORNER_EPSILON: f64 = 1e-9;
+
+/// 沿速度方向到达边界所需的时间；已越过边界且仍向外运动时为 0
+fn time_to_wall(pos: f64, vel: f64, min: f64, max: f64) -> f64 {
+    if vel > 0.0 {
+        ((max - pos) / vel).max(0.0)
+    } else if vel < 0.0 {
+        ((min - pos) / vel).max(0.0)
+    } else {
+        f64::INFINITY
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Wall {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Wall {
+    fn combine(horizontal: Option<Wall>, vertical: Option<Wall>) -> Wall {
+        match (horizontal, vertical) {
+            (Some(Wall::Left), Some(Wall::Top)) => Wall::TopLeft,
+            (Some(Wall::Right), Some(Wall::Top)) => Wall::TopRight,
+            (Some(Wall::Left), Some(Wall::Bottom)) => Wall::BottomLeft,
+            (Some(Wall::Right), Some(Wall::Bottom)) => Wall::BottomRight,
+            (Some(wall), _) | (None, Some(wall)) => wall,
+            (None, None) => unreachable!("a bounce hits at least one wall"),
+        }
+    }
+}
+
+/// 一次反弹：碰撞位置与相对本步起点的时间
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Bounce {
+    pub x: f64,
+    pub y: f64,
+    pub time: f64,
+    pub wall: Wall,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,8 +294,94 @@ mod tests {
         ball.set_speed(1000.0, Some((1.0, 0.0)));
         ball.x = 95.0;
 
-        let bounced = ball.update(0.01).unwrap();
-        assert!(bounced);
+        let bounces = ball.update(0.01).unwrap();
+        assert!(!bounces.is_empty());
+    }
+
+    #[test]
+    fn test_lissajous_stays_inside_walls() {
+        let mut ball = Ball::new(1920.0, 1080.0);
+        let r = ball.radius;
+        for i in 0..4000 {
+            ball.update_lissajous(i as f64 * 0.005, 0.25, 0.5, PI / 2.0, 1.0);
+            assert!(ball.x >= r - 1e-9 && ball.x <= 1920.0 - r + 1e-9);
+            assert!(ball.y >= r - 1e-9 && ball.y <= 1080.0 - r + 1e-9);
+        }
+
+        // 一个完整周期 (1 / 0.25 = 4s) 后回到起点
+        ball.update_lissajous(0.0, 0.25, 0.5, PI / 2.0, 1.0);
+        let start = (ball.x, ball.y);
+        ball.update_lissajous(4.0, 0.25, 0.5, PI / 2.0, 1.0);
+        assert!((ball.x - start.0).abs() < 1e-6 && (ball.y - start.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_scale_keeps_ball_inside_walls() {
+        let mut ball = Ball::new(1000.0, 900.0); // radius 40
+        ball.x = 955.0;
+        ball.set_speed(500.0, Some((1.0, 0.0)));
+
+        ball.set_scale(2.0);
+        assert_eq!(ball.radius, 80.0);
+        assert_eq!(ball.x, 920.0);
+
+        let bounces = ball.update(0.01).unwrap();
+        assert_eq!(bounces.len(), 1);
+        assert_eq!(bounces[0].time, 0.0);
+        assert!(ball.vx < 0.0);
+    }
+
+    #[test]
+    fn test_swept_collision_keeps_remaining_time() {
+        let mut ball = Ball::new(1000.0, 900.0); // radius 40
+        ball.x = 900.0;
+        ball.y = 450.0;
+        ball.set_speed(1000.0, Some((1.0, 0.0)));
+
+        // 0.06s 后撞到右墙 (x = 960)，剩余 0.04s 反向运动 40px
+        let bounces = ball.update(0.1).unwrap();
+        assert_eq!(bounces.len(), 1);
+        assert_eq!(bounces[0].wall, Wall::Right);
+        assert_eq!(bounces[0].x, 960.0);
+        assert!((bounces[0].time - 0.06).abs() < 1e-12);
+        assert!((ball.x - 920.0).abs() < 1e-9);
+        assert_eq!(ball.vx, -1000.0);
+    }
+
+    #[test]
+    fn test_swept_collision_corner_hit() {
+        let mut ball = Ball::new(1000.0, 900.0); // radius 40
+        ball.x = 900.0;
+        ball.y = 800.0;
+        ball.set_speed(1000.0 * 2f64.sqrt(), Some((1.0, 1.0)));
+
+        let bounces = ball.update(0.1).unwrap();
+        assert_eq!(bounces.len(), 1);
+        assert_eq!(bounces[0].wall, Wall::BottomRight);
+        assert_eq!((bounces[0].x, bounces[0].y), (960.0, 860.0));
+        assert!(ball.vx < 0.0 && ball.vy < 0.0);
+    }
+
+    #[test]
+    fn test_swept_collision_multiple_bounces() {
+        let mut ball = Ball::new(200.0, 900.0); // radius 40, x 范围 40..160
+        ball.x = 100.0;
+        ball.y = 450.0;
+        ball.set_speed(1200.0, Some((1.0, 0.0)));
+
+        // 0.05s 到右墙，再 0.1s 到左墙，剩余 0.05s
+        let bounces = ball.update(0.2).unwrap();
+        let walls: Vec<Wall> = bounces.iter().map(|b| b.wall).collect();
+        assert_eq!(walls, vec![Wall::Right, Wall::Left]);
+        assert!((bounces[1].time - 0.15).abs() < 1e-12);
+        assert!((ball.x - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_random_pos_on_tiny_screen() {
+        let mut ball = Ball::new(1000.0, 800.0);
+        ball.reset_to_random_pos(80.0, 60.0, &mut rand::thread_rng());
+        assert_eq!((ball.x, ball.y), (40.0, 30.0));
     }
 
     #[test]
@@ -1,87 +1,256 @@
-use crate::{AudioParams, Result};
+use crate::program::DEFAULT_PROGRAM_NAME;
+use crate::{AudioParams, CoreError, GazeConfig, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+pub(crate) const CONFIG_FILE: &str = "config.json";
+
+/// 一步迁移：把某个版本的配置 JSON 升级到下一个版本
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// `MIGRATIONS[i]` 把版本 `i` 的配置升级到 `i + 1`；修改配置结构时在末尾追加一步
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+/// 当前配置版本；没有 `version` 字段的旧文件视为版本 0
+pub const CONFIG_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// 缺少的字段使用默认值，新增字段不会导致整个配置被丢弃
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UserConfig {
+    pub version: u32,
     pub audio: AudioParams,
     pub language: String,
     pub last_played_stage: i32,
+    pub selected_program: String,
+    pub gaze: GazeConfig,
+    /// 读取自哪个文件，`save` 写回同一个文件（用于用户档案）
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// 不认识的字段（如新版本写入的设置），保存时原样写回
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 impl Default for UserConfig {
     fn default() -> Self {
         UserConfig {
+            version: CONFIG_VERSION,
             audio: AudioParams::default(),
             language: "en".to_string(),
             last_played_stage: 1,
+            selected_program: DEFAULT_PROGRAM_NAME.to_string(),
+            gaze: GazeConfig::default(),
+            path: None,
+            extra: Map::new(),
         }
     }
 }
 
-impl UserConfig {
-    fn get_config_path() -> Result<PathBuf> {
-        #[cfg(target_os = "windows")]
-        {
-            if let Ok(appdata) = std::env::var("APPDATA") {
-                let mut path = PathBuf::from(appdata);
-                path.push("eyemotion");
-                return Ok(path);
-            }
+/// 版本 0 的 `last_played_stage` 可能为 0 或负数（旧版本在开始界面保存），修正为 1
+fn migrate_v0_to_v1(config: &mut Map<String, Value>) -> Result<()> {
+    if let Some(stage) = config.get("last_played_stage").and_then(Value::as_i64) {
+        if stage < 1 {
+            config.insert("last_played_stage".to_string(), Value::from(1));
         }
+    }
+    Ok(())
+}
 
-        #[cfg(target_os = "macos")]
-        {
-            if let Ok(home) = std::env::var("HOME") {
-                let mut path = PathBuf::from(home);
-                path.push("Library/Application Support/eyemotion");
-                return Ok(path);
-            }
+/// 把配置 JSON 从 `version` 逐步升级到 [`CONFIG_VERSION`]
+fn migrate(config: &mut Map<String, Value>, version: u32) -> Result<()> {
+    for (step, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(config).map_err(|e| CoreError::Config(format!("Migration from version {} failed: {}", step, e)))?;
+        config.insert("version".to_string(), Value::from(step as u32 + 1));
+    }
+    Ok(())
+}
+
+/// 在配置旁边保存一份原文件，如 `config.json.v0.bak`
+fn backup(path: &Path, suffix: &str) -> Option<PathBuf> {
+    let mut name = path.file_name()?.to_owned();
+    name.push(format!(".{}.bak", suffix));
+    let backup = path.with_file_name(name);
+    match fs::copy(path, &backup) {
+        Ok(_) => Some(backup),
+        Err(e) => {
+            log::warn!("Failed to back up {}: {}", path.display(), e);
+            None
         }
+    }
+}
 
-        #[cfg(target_os = "linux")]
-        {
-            if let Ok(home) = std::env::var("HOME") {
-                let mut path = PathBuf::from(home);
-                path.push(".config/eyemotion");
-                return Ok(path);
-            }
+/// 由外部指定的配置目录（Tauri 的应用数据目录或测试用的临时目录），优先于平台默认目录
+static CONFIG_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// 平台默认的配置目录；移动平台没有可用的默认目录，需要通过 [`UserConfig::set_config_dir`] 指定
+fn default_config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    return env_dir("APPDATA").map(|path| path.join("eyemotion"));
+
+    #[cfg(target_os = "macos")]
+    return env_dir("HOME").map(|path| path.join("Library/Application Support/eyemotion"));
+
+    // XDG 规范要求 XDG_CONFIG_HOME 为绝对路径，否则忽略
+    #[cfg(target_os = "linux")]
+    return env_dir("XDG_CONFIG_HOME")
+        .filter(|path| path.is_absolute())
+        .or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+        .map(|path| path.join("eyemotion"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    None
+}
+
+/// 本进程内临时文件的序号，与进程号一起保证同时写入时临时文件名不冲突
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 先写入同目录的临时文件并同步到磁盘，再改名覆盖，保证文件要么是旧内容要么是新内容
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}-{}.tmp", std::process::id(), TMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let tmp = PathBuf::from(tmp);
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+impl UserConfig {
+    /// 指定配置目录，之后的配置、训练程序、录制与历史都保存在这里
+    pub fn set_config_dir(dir: impl Into<PathBuf>) {
+        let mut config_dir = CONFIG_DIR.write().unwrap_or_else(|e| e.into_inner());
+        *config_dir = Some(dir.into());
+    }
+
+    /// 配置目录：已指定时使用指定的目录，否则使用平台默认目录
+    pub fn get_config_path() -> Result<PathBuf> {
+        let config_dir = CONFIG_DIR.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(dir) = config_dir.as_ref() {
+            return Ok(dir.clone());
         }
+        default_config_dir().ok_or_else(|| CoreError::Config("No config directory available on this platform".to_string()))
+    }
 
-        #[cfg(any(target_os = "ios", target_os = "android"))]
-        {
-            // Mobile platforms don't use file-based config in the same way, 
-            // or should use Tauri's path resolver if needed. 
-            // For now, return a safe dummy path to avoid errors.
-            return Ok(PathBuf::from("."));
+    pub fn load() -> Self {
+        match Self::get_config_path() {
+            Ok(config_dir) => Self::load_from(&config_dir.join(CONFIG_FILE)),
+            Err(e) => {
+                log::warn!("Using default config: {}", e);
+                UserConfig::default()
+            }
         }
+    }
 
-        Ok(PathBuf::from("."))
+    /// 读取配置文件并按需迁移；文件损坏时备份原文件、记录日志并使用默认配置
+    pub fn load_from(path: &Path) -> Self {
+        let mut config = if path.exists() { Self::read_or_default(path) } else { UserConfig::default() };
+        config.path = Some(path.to_path_buf());
+        config
     }
 
-    pub fn load() -> Self {
-        if let Ok(config_dir) = Self::get_config_path() {
-            let config_file = config_dir.join("config.json");
-            if config_file.exists() {
-                if let Ok(content) = fs::read_to_string(&config_file) {
-                    if let Ok(config) = serde_json::from_str::<UserConfig>(&content) {
-                        return config;
-                    }
+    fn read_or_default(path: &Path) -> Self {
+        match Self::read(path) {
+            Ok(config) => config,
+            Err(e) => {
+                let stamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                match backup(path, &format!("corrupt-{}", stamp)) {
+                    Some(backup) => log::error!(
+                        "Config file {} is corrupt ({}); using defaults, original saved to {}",
+                        path.display(),
+                        e,
+                        backup.display()
+                    ),
+                    None => log::error!("Config file {} is corrupt ({}); using defaults", path.display(), e),
                 }
+                UserConfig::default()
             }
         }
-        UserConfig::default()
     }
 
+    fn read(path: &Path) -> Result<Self> {
+        let value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let Value::Object(mut map) = value else {
+            return Err(CoreError::Config("Config root is not an object".to_string()));
+        };
+        let version = match map.get("version") {
+            None => 0,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| CoreError::Config(format!("Invalid config version: {}", v)))?,
+        };
+
+        if version > CONFIG_VERSION {
+            // 新版本写的配置：读取认识的字段，不认识的字段保存在 `extra` 中，保存时不会丢失
+            log::warn!(
+                "Config file {} has version {}, newer than supported version {}",
+                path.display(),
+                version,
+                CONFIG_VERSION
+            );
+            return Ok(serde_json::from_value(Value::Object(map))?);
+        }
+        if version == CONFIG_VERSION {
+            return Ok(serde_json::from_value(Value::Object(map))?);
+        }
+
+        let backup = backup(path, &format!("v{}", version));
+        migrate(&mut map, version)?;
+        let config: UserConfig = serde_json::from_value(Value::Object(map))?;
+        log::info!(
+            "Migrated config {} from version {} to {}{}",
+            path.display(),
+            version,
+            CONFIG_VERSION,
+            backup.map(|b| format!(", original saved to {}", b.display())).unwrap_or_default()
+        );
+        if let Err(e) = config.save_to(path) {
+            log::warn!("Failed to save migrated config: {}", e);
+        }
+        Ok(config)
+    }
+
+    /// 保存到读取时的文件，没有时保存到配置目录中的 `config.json`
     pub fn save(&self) -> Result<()> {
-        if let Ok(config_dir) = Self::get_config_path() {
-            fs::create_dir_all(&config_dir)?;
-            let config_file = config_dir.join("config.json");
-            let json_string = serde_json::to_string_pretty(self)?;
-            fs::write(config_file, json_string)?;
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => Self::get_config_path()?.join(CONFIG_FILE),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
         }
-        Ok(())
+        self.save_to(&path)
+    }
+
+    /// 配置文件路径，未从文件读取时为空
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 写入临时文件后改名，保存过程中崩溃不会留下不完整的配置
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let json_string = serde_json::to_string_pretty(self)?;
+        write_atomic(path, json_string.as_bytes())
     }
 
     pub fn update_last_stage(&mut self, stage: i32) {
@@ -90,3 +259,128 @@ impl UserConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eyemotion-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_migrates_legacy_config() {
+        // 版本 0：没有 version、selected_program 与 gaze，音频配置完整
+        let mut legacy = serde_json::to_value(UserConfig::default()).unwrap();
+        let map = legacy.as_object_mut().unwrap();
+        map.remove("version");
+        map.remove("selected_program");
+        map.remove("gaze");
+        map.insert("language".to_string(), Value::from("zh"));
+        map.insert("last_played_stage".to_string(), Value::from(0));
+        let path = temp_config("legacy", &legacy.to_string());
+
+        let config = UserConfig::load_from(&path);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.language, "zh");
+        assert_eq!(config.last_played_stage, 1);
+        assert_eq!(config.selected_program, DEFAULT_PROGRAM_NAME);
+
+        let backup = path.with_file_name("config.json.v0.bak");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), legacy.to_string());
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], CONFIG_VERSION);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_missing_fields_keep_settings() {
+        let path = temp_config("partial", r#"{"version": 1, "language": "zh", "future_field": true}"#);
+        let config = UserConfig::load_from(&path);
+        assert_eq!(config.language, "zh");
+        assert_eq!(config.last_played_stage, 1);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_newer_config_keeps_unknown_fields() {
+        let newer = CONFIG_VERSION + 1;
+        let path = temp_config(
+            "newer",
+            &format!(r#"{{"version": {}, "language": "zh", "theme": {{"dark": true}}, "volume_curve": [1, 2]}}"#, newer),
+        );
+        let mut config = UserConfig::load_from(&path);
+        assert_eq!(config.version, newer);
+        assert_eq!(config.language, "zh");
+
+        config.language = "en".to_string();
+        config.save().unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], newer);
+        assert_eq!(saved["language"], "en");
+        assert_eq!(saved["theme"], serde_json::json!({"dark": true}));
+        assert_eq!(saved["volume_curve"], serde_json::json!([1, 2]));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_atomic_save() {
+        let path = temp_config("save", "{}");
+        let config = UserConfig { language: "zh".to_string(), ..UserConfig::default() };
+        config.save_to(&path).unwrap();
+        assert_eq!(UserConfig::load_from(&path).language, "zh");
+        let files: Vec<_> = fs::read_dir(path.parent().unwrap()).unwrap().filter_map(|e| e.ok()).collect();
+        assert_eq!(files.len(), 1);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_concurrent_atomic_writes() {
+        let path = temp_config("concurrent", "{}");
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        write_atomic(&path, format!("{{\"writer\": {}}}", i).as_bytes()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved["writer"].is_u64());
+        let leftovers = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_config_is_backed_up() {
+        let path = temp_config("corrupt", r#"{"language": "zh", "#);
+        let config = UserConfig::load_from(&path);
+        assert_eq!(config.language, "en");
+
+        let backups: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("config.json.corrupt-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        // 原文件保持不变，直到下一次保存
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"language": "zh", "#);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
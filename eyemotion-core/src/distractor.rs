@@ -0,0 +1,64 @@
+use crate::game_state::EDGE_MARGIN;
+use crate::{Ball, Bounce, DistractorMotion, Result};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// 干扰球：与目标同屏运动，但不是需要追踪的对象
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Distractor {
+    pub ball: Ball,
+    pub motion: DistractorMotion,
+    /// 上一物理步的位置，配合 `GameState::interpolation.alpha` 插值
+    pub prev_x: f64,
+    pub prev_y: f64,
+}
+
+impl Distractor {
+    /// 在随机位置生成干扰球，并按运动方式设置初速度
+    pub fn spawn<R: Rng>(screen_w: f64, screen_h: f64, motion: DistractorMotion, rng: &mut R) -> Self {
+        let mut ball = Ball::new(screen_w, screen_h);
+        ball.reset_to_random_pos(screen_w, screen_h, rng);
+
+        if let DistractorMotion::Linear { speed, angle_range: (lo, hi) } = motion {
+            let angle_deg = if lo < hi { rng.gen_range(lo..hi) } else { lo };
+            let angle_rad = angle_deg * PI / 180.0;
+            let vx = if rng.gen_bool(0.5) { angle_rad.cos() } else { -angle_rad.cos() };
+            let vy = if rng.gen_bool(0.5) { angle_rad.sin() } else { -angle_rad.sin() };
+            ball.set_speed(speed, Some((vx, vy)));
+        }
+
+        Distractor {
+            prev_x: ball.x,
+            prev_y: ball.y,
+            ball,
+            motion,
+        }
+    }
+
+    /// 是否自由运动（可被小球间碰撞改变速度）
+    pub fn is_free(&self) -> bool {
+        matches!(self.motion, DistractorMotion::Linear { .. })
+    }
+
+    /// 推进一个物理步，返回与边界的反弹
+    pub fn advance(&mut self, dt: f64, stage_time: f64) -> Result<Vec<Bounce>> {
+        self.prev_x = self.ball.x;
+        self.prev_y = self.ball.y;
+
+        match self.motion {
+            DistractorMotion::Linear { .. } => self.ball.update(dt),
+            DistractorMotion::Circular { angular_speed, radius_ratio } => {
+                let max_radius =
+                    ((self.ball.screen_h - 2.0 * (self.ball.radius + EDGE_MARGIN)) / 2.0).max(0.0);
+                self.ball
+                    .update_circular_with_radius(dt, angular_speed, max_radius * radius_ratio);
+                Ok(Vec::new())
+            }
+            DistractorMotion::Lissajous { freq_x, freq_y, phase, amplitude } => {
+                self.ball
+                    .update_lissajous(stage_time, freq_x, freq_y, phase, amplitude);
+                Ok(Vec::new())
+            }
+        }
+    }
+}
@@ -1,9 +1,22 @@
+use crate::ball::Wall;
+use crate::analysis::StageReport;
+use crate::tracking::TrackingReport;
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "type")]
 pub enum GameEvent {
-    BallBounced,
+    /// 小球碰到边界；`ball` 为 0 表示目标，`i + 1` 表示第 i 个干扰球，
+    /// `stage_time` 为碰撞发生时距关卡开始的秒数
+    BallBounced { ball: usize, x: f64, y: f64, wall: Wall, stage_time: f64 },
+    /// 两个小球相撞（编号规则同 `BallBounced`），`x`/`y` 为接触点
+    BallsCollided { a: usize, b: usize, x: f64, y: f64, stage_time: f64 },
+    /// 扫视模式下目标从 `from` 跳到 `to`；`response` 为正确的注视位置，
+    /// 反扫视时是 `to` 的水平镜像，其余规则与 `to` 相同
+    TargetJumped { from: (f64, f64), to: (f64, f64), response: (f64, f64), stage_time: f64 },
     StageChanged { from: i32, to: i32 },
-    StageCompleted { stage: i32 },
+    /// 关卡时间用完；`tracking` 为本关的指针跟踪汇总（没有指针输入时为空），
+    /// `report` 为指针与眼动的跟踪分析
+    StageCompleted { stage: i32, tracking: Option<TrackingReport>, report: Box<StageReport> },
     GameOver,
 }
 
@@ -11,4 +24,8 @@ pub enum GameEvent {
 pub struct GameUpdate {
     pub events: Vec<GameEvent>,
     pub time_elapsed: f64,
+    /// 本次实际推进的模拟时间（秒），超出追帧上限而丢弃的时间不计入
+    pub stepped: f64,
+    /// 当前关卡的实时跟踪统计
+    pub tracking: Option<TrackingReport>,
 }
@@ -0,0 +1,317 @@
+//! 导出训练历史，供视光师在电子表格中查看。
+//!
+//! CSV 导出写两个文件（UTF-8，逗号分隔，首行为列名），列的顺序固定，新增列只会加在末尾：
+//!
+//! `*-sessions.csv`，每次训练一行：
+//!
+//! | 列 | 含义 |
+//! |---|---|
+//! | `session_id` | 训练编号 |
+//! | `date` | 开始日期（本地，`YYYY-MM-DD`） |
+//! | `start_time` / `end_time` | 开始与结束时间（本地，ISO 8601，带时区） |
+//! | `program` | 训练程序名称 |
+//! | `duration_minutes` | 实际训练时长（分钟，不含暂停） |
+//! | `pauses` | 暂停次数 |
+//! | `stages_completed` | 完成的关卡数 |
+//! | `mean_score` | 各关得分平均值（0–100），没有得分时为空 |
+//! | `finished` | 是否完成全部关卡（`true` / `false`） |
+//!
+//! `*-stages.csv`，每个完成的关卡一行：`session_id`、`date`、`program`、`stage`、`score`（没有得分时为空）。
+//!
+//! JSON 导出为单个文件：`{"version": 1, "utc_offset_minutes": …, "sessions": [...]}`，
+//! 每个会话包含上面的会话列，以及 `stages` 数组（每项为 `stage` 与 `score`）。
+
+use crate::history::{format_day, format_local_time, local_day, parse_day};
+use crate::{History, Result, SessionRecord, StageResult};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// 导出格式版本，列或字段的含义改变时递增
+pub const EXPORT_VERSION: u32 = 1;
+pub const SESSION_COLUMNS: [&str; 10] = [
+    "session_id",
+    "date",
+    "start_time",
+    "end_time",
+    "program",
+    "duration_minutes",
+    "pauses",
+    "stages_completed",
+    "mean_score",
+    "finished",
+];
+pub const STAGE_COLUMNS: [&str; 5] = ["session_id", "date", "program", "stage", "score"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// 导出选项；日期为本地日期 `YYYY-MM-DD`，包含首尾两天，为空时不限制
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    /// 本地时区相对 UTC 的分钟数
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+/// JSON 导出中的一次训练
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedSession {
+    pub session_id: u64,
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub program: String,
+    pub duration_minutes: f64,
+    pub pauses: u32,
+    pub stages_completed: u32,
+    pub mean_score: Option<f64>,
+    pub finished: bool,
+    pub stages: Vec<StageResult>,
+}
+
+impl ExportedSession {
+    fn new(record: &SessionRecord, utc_offset_minutes: i32) -> Self {
+        ExportedSession {
+            session_id: record.id,
+            date: format_day(local_day(record.started, utc_offset_minutes)),
+            start_time: format_local_time(record.started, utc_offset_minutes),
+            end_time: format_local_time(record.ended, utc_offset_minutes),
+            program: record.program.clone(),
+            duration_minutes: record.duration / 60.0,
+            pauses: record.pauses,
+            stages_completed: record.stages_completed.len() as u32,
+            mean_score: record.mean_score(),
+            finished: record.finished,
+            stages: record.stages_completed.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryExport {
+    pub version: u32,
+    pub utc_offset_minutes: i32,
+    pub sessions: Vec<ExportedSession>,
+}
+
+impl ExportOptions {
+    /// 按日期范围筛选训练
+    pub fn filter<'a>(&self, records: &'a [SessionRecord]) -> Result<Vec<&'a SessionRecord>> {
+        let from = self.from.as_deref().map(parse_day).transpose()?;
+        let to = self.to.as_deref().map(parse_day).transpose()?;
+        Ok(records
+            .iter()
+            .filter(|r| {
+                let day = local_day(r.started, self.utc_offset_minutes);
+                from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+            })
+            .collect())
+    }
+
+    pub fn to_export(&self, records: &[SessionRecord]) -> Result<HistoryExport> {
+        Ok(HistoryExport {
+            version: EXPORT_VERSION,
+            utc_offset_minutes: self.utc_offset_minutes,
+            sessions: self
+                .filter(records)?
+                .into_iter()
+                .map(|r| ExportedSession::new(r, self.utc_offset_minutes))
+                .collect(),
+        })
+    }
+}
+
+impl History {
+    /// 按 `options` 把历史导出到目录 `dir`，返回写入的文件
+    pub fn export(&self, options: &ExportOptions, dir: &Path) -> Result<Vec<PathBuf>> {
+        let export = options.to_export(&self.list()?)?;
+        fs::create_dir_all(dir)?;
+
+        let mut name = String::from("eyemotion-history");
+        for date in [&options.from, &options.to].into_iter().flatten() {
+            name.push('-');
+            name.push_str(date.trim());
+        }
+
+        let paths = match options.format {
+            ExportFormat::Csv => {
+                let sessions = dir.join(format!("{}-sessions.csv", name));
+                let stages = dir.join(format!("{}-stages.csv", name));
+                write_file(&sessions, |out| write_sessions_csv(&export, out))?;
+                write_file(&stages, |out| write_stages_csv(&export, out))?;
+                vec![sessions, stages]
+            }
+            ExportFormat::Json => {
+                let path = dir.join(format!("{}.json", name));
+                write_file(&path, |out| Ok(serde_json::to_writer_pretty(out, &export)?))?;
+                vec![path]
+            }
+        };
+        Ok(paths)
+    }
+}
+
+fn write_file(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> Result<()>) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// 只在需要时加引号，避免程序名中的逗号或引号破坏列
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn optional(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+pub fn write_sessions_csv<W: Write>(export: &HistoryExport, mut out: W) -> Result<()> {
+    writeln!(out, "{}", SESSION_COLUMNS.join(","))?;
+    for s in &export.sessions {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{}",
+            s.session_id,
+            s.date,
+            s.start_time,
+            s.end_time,
+            csv_field(&s.program),
+            s.duration_minutes,
+            s.pauses,
+            s.stages_completed,
+            optional(s.mean_score),
+            s.finished
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write_stages_csv<W: Write>(export: &HistoryExport, mut out: W) -> Result<()> {
+    writeln!(out, "{}", STAGE_COLUMNS.join(","))?;
+    for s in &export.sessions {
+        for stage in &s.stages {
+            writeln!(
+                out,
+                "{},{},{},{},{}",
+                s.session_id,
+                s.date,
+                csv_field(&s.program),
+                stage.stage,
+                optional(stage.score)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: u64, date: &str, program: &str, scores: &[Option<f64>]) -> SessionRecord {
+        let started = parse_day(date).unwrap() as u64 * 86_400 + 9 * 3600;
+        SessionRecord {
+            id,
+            started,
+            ended: started + 600,
+            program: program.to_string(),
+            stages_completed: scores
+                .iter()
+                .enumerate()
+                .map(|(i, score)| StageResult { stage: i as i32 + 1, score: *score })
+                .collect(),
+            duration: 540.0,
+            pauses: 1,
+            finished: scores.len() == 3,
+        }
+    }
+
+    fn records() -> Vec<SessionRecord> {
+        vec![
+            record(1, "2024-03-01", "default", &[Some(80.0), Some(90.0), None]),
+            record(2, "2024-03-05", "smooth, slow", &[None]),
+            record(3, "2024-03-09", "default", &[]),
+        ]
+    }
+
+    fn options(from: Option<&str>, to: Option<&str>) -> ExportOptions {
+        ExportOptions {
+            format: ExportFormat::Csv,
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            utc_offset_minutes: 0,
+        }
+    }
+
+    #[test]
+    fn test_date_filter() {
+        let records = records();
+        let ids = |o: ExportOptions| o.filter(&records).unwrap().iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(options(None, None)), [1, 2, 3]);
+        assert_eq!(ids(options(Some("2024-03-05"), None)), [2, 3]);
+        assert_eq!(ids(options(Some("2024-03-01"), Some("2024-03-05"))), [1, 2]);
+        // 09:00 UTC 在 UTC-10 是前一天
+        assert_eq!(ids(ExportOptions { utc_offset_minutes: -600, ..options(None, Some("2024-03-04")) }), [1, 2]);
+        assert!(options(Some("March 1"), None).filter(&records).is_err());
+    }
+
+    #[test]
+    fn test_csv_layout() {
+        let export = options(None, Some("2024-03-05")).to_export(&records()).unwrap();
+        let mut sessions = Vec::new();
+        write_sessions_csv(&export, &mut sessions).unwrap();
+        let sessions = String::from_utf8(sessions).unwrap();
+        let lines: Vec<&str> = sessions.lines().collect();
+        assert_eq!(lines[0], SESSION_COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            "1,2024-03-01,2024-03-01T09:00:00+00:00,2024-03-01T09:10:00+00:00,default,9,1,3,85,true"
+        );
+        assert!(lines[2].contains(",\"smooth, slow\",9,1,1,,false"), "{}", lines[2]);
+
+        let mut stages = Vec::new();
+        write_stages_csv(&export, &mut stages).unwrap();
+        let stages = String::from_utf8(stages).unwrap();
+        assert_eq!(stages.lines().count(), 1 + 4);
+        assert_eq!(stages.lines().nth(3).unwrap(), "1,2024-03-01,default,3,");
+    }
+
+    #[test]
+    fn test_export_files() {
+        let dir = std::env::temp_dir().join(format!("eyemotion-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("config")).unwrap();
+        let lines: Vec<String> = records().iter().map(|r| serde_json::to_string(r).unwrap()).collect();
+        fs::write(dir.join("config/history.jsonl"), lines.join("\n")).unwrap();
+        let history = History::open_in(dir.join("config")).unwrap();
+
+        let paths = history.export(&options(None, None), &dir).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap().lines().count(), 4);
+
+        let json_options = ExportOptions { format: ExportFormat::Json, ..options(Some("2024-03-02"), None) };
+        let paths = history.export(&json_options, &dir).unwrap();
+        assert!(paths[0].ends_with("eyemotion-history-2024-03-02.json"));
+        let export: HistoryExport = serde_json::from_str(&fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        assert_eq!(export.version, EXPORT_VERSION);
+        assert_eq!(export.sessions.len(), 2);
+        assert_eq!(export.sessions[0].program, "smooth, slow");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,35 @@
-use crate::{Ball, GameEvent, GameUpdate, Result};
-use rand::Rng;
-use std::f64::consts::PI;
+use crate::{
+    AnalysisParams, Ball, Bounce, CoreError, Distractor, GameEvent, GameUpdate, GazeSample, GazeSpace, GazeTracker, JumpRule, MotionType,
+    PointerSample, Result, StageConfig, StageReport, Tracker, Trajectory, TrainingProgram,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-pub const STAGE_DURATIONS_MS: u64 = 45_000;
+/// 圆周运动与扫视目标距离边界的留白 (相对)
+pub(crate) const EDGE_MARGIN: f64 = 16.0;
+/// 事件中目标小球的编号，干扰球依次为 1, 2, ...
+pub const TARGET_BALL: usize = 0;
+/// 圆周运动半程反向前的停顿时长
+const ORBIT_REVERSE_PAUSE: f64 = 0.3;
+/// 默认物理步长 (240 Hz)
+pub const DEFAULT_FIXED_DT: f64 = 1.0 / 240.0;
+/// 单次 update 最多追赶的物理步数，超出部分直接丢弃，避免卡顿后画面跳跃
+pub const DEFAULT_MAX_CATCH_UP_STEPS: u32 = 30;
+
+/// 供渲染器在两次物理步之间插值：`prev + (ball - prev) * alpha`
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct Interpolation {
+    pub prev_x: f64,
+    pub prev_y: f64,
+    pub alpha: f64,
+}
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GameState {
+    /// 需要追踪的目标小球
     pub ball: Ball,
+    pub distractors: Vec<Distractor>,
+    pub program: TrainingProgram,
     pub stage: i32,
     pub stage_elapsed: f64,
     pub paused: bool,
@@ -14,36 +37,180 @@ pub struct GameState {
     pub transition_timer: f64,
     pub is_game_over: bool,
     pub is_start_screen: bool,
-    pub stage5_paused: bool,
-    pub stage5_pause_elapsed: f64,
+    pub orbit_paused: bool,
+    pub orbit_pause_elapsed: f64,
+    /// 扫视模式：当前目标已停留的时间与本关已跳跃的次数
+    pub saccade_timer: f64,
+    pub saccade_jumps: u32,
+    /// 本次训练的随机种子，相同种子与输入可完整复现运动轨迹
+    pub seed: u64,
+    rng: ChaCha8Rng,
+    pub fixed_dt: f64,
+    pub max_catch_up_steps: u32,
+    pub accumulator: f64,
+    pub interpolation: Interpolation,
+    /// 当前关卡的目标轨迹与指针 / 眼动统计，只在当前关卡内有效，不随状态序列化
+    #[serde(skip)]
+    pub trajectory: Trajectory,
+    #[serde(skip)]
+    pub tracking: Tracker,
+    #[serde(skip)]
+    pub gaze: GazeTracker,
 }
 
 impl GameState {
     pub fn new(w: f64, h: f64) -> Self {
-        let mut ball = Ball::new(w, h);
-        ball.set_speed(stage_speed(1), Some(stage_direction(1)));
-        GameState {
-            ball,
+        Self::with_program(w, h, TrainingProgram::default())
+            .expect("default program is valid")
+    }
+
+    pub fn with_program(w: f64, h: f64, program: TrainingProgram) -> Result<Self> {
+        Self::with_seed(w, h, program, random_seed())
+    }
+
+    pub fn with_seed(w: f64, h: f64, program: TrainingProgram, seed: u64) -> Result<Self> {
+        program.validate()?;
+        let transition_timer = program.transition_duration;
+        let mut state = GameState {
+            ball: Ball::new(w, h),
+            distractors: Vec::new(),
+            program,
             stage: 1,
             stage_elapsed: 0.0,
             paused: false,
             is_transitioning: true,
-            transition_timer: 3.0, // 所有关卡统一为 3.0s 倒计时
+            transition_timer,
             is_game_over: false,
             is_start_screen: true,
-            stage5_paused: false,
-            stage5_pause_elapsed: 0.0,
+            orbit_paused: false,
+            orbit_pause_elapsed: 0.0,
+            saccade_timer: 0.0,
+            saccade_jumps: 0,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            fixed_dt: DEFAULT_FIXED_DT,
+            max_catch_up_steps: DEFAULT_MAX_CATCH_UP_STEPS,
+            accumulator: 0.0,
+            interpolation: Interpolation::default(),
+            trajectory: Trajectory::default(),
+            tracking: Tracker::default(),
+            gaze: GazeTracker::default(),
+        };
+        state.apply_stage_motion();
+        Ok(state)
+    }
+
+    /// 切换训练程序并回到第一关，保留当前屏幕尺寸
+    pub fn set_program(&mut self, program: TrainingProgram) -> Result<()> {
+        program.validate()?;
+        self.program = program;
+        self.reset(self.ball.screen_w, self.ball.screen_h);
+        Ok(())
+    }
+
+    pub fn stage_count(&self) -> i32 {
+        self.program.stages.len() as i32
+    }
+
+    pub fn current_stage(&self) -> &StageConfig {
+        &self.program.stages[self.stage_index()]
+    }
+
+    fn stage_index(&self) -> usize {
+        (self.stage - 1).clamp(0, self.stage_count() - 1) as usize
+    }
+
+    /// 设置物理步长（秒），例如 `1.0 / 240.0`
+    pub fn set_fixed_dt(&mut self, fixed_dt: f64) -> Result<()> {
+        if !fixed_dt.is_finite() || fixed_dt <= 0.0 {
+            return Err(CoreError::Physics(format!("invalid fixed timestep: {}", fixed_dt)));
         }
+        self.fixed_dt = fixed_dt;
+        self.accumulator = 0.0;
+        Ok(())
     }
 
+    /// 推进真实经过的时间 `dt`：按固定步长执行物理步，余量留到下一帧
     pub fn update(&mut self, dt: f64) -> Result<GameUpdate> {
         let mut events = Vec::new();
 
+        self.accumulator += dt.max(0.0);
+        let mut steps = 0;
+        // 容差用于吸收浮点累加误差，保证不同帧率下步数一致
+        while self.accumulator + 1e-9 >= self.fixed_dt {
+            if steps >= self.max_catch_up_steps {
+                self.accumulator = 0.0;
+                break;
+            }
+            self.interpolation.prev_x = self.ball.x;
+            self.interpolation.prev_y = self.ball.y;
+            self.step(self.fixed_dt, &mut events)?;
+            self.accumulator -= self.fixed_dt;
+            steps += 1;
+        }
+        self.accumulator = self.accumulator.max(0.0);
+        self.interpolation.alpha = (self.accumulator / self.fixed_dt).min(1.0);
+
+        Ok(GameUpdate {
+            events,
+            time_elapsed: self.stage_elapsed,
+            stepped: steps as f64 * self.fixed_dt,
+            tracking: self.tracking.live_report(&self.trajectory),
+        })
+    }
+
+    /// 当前关卡到目前为止的跟踪分析
+    pub fn stage_report(&self) -> StageReport {
+        StageReport::analyze(
+            self.stage,
+            self.trajectory.samples(),
+            self.tracking.samples(),
+            self.gaze.samples(),
+            &AnalysisParams::for_screen_height(self.ball.screen_h),
+        )
+    }
+
+    /// 关卡正在进行（可以计入指针 / 眼动采样）
+    fn is_stage_active(&self) -> bool {
+        !(self.is_game_over || self.is_start_screen || self.paused || self.is_transitioning)
+    }
+
+    /// 记录一个指针采样（逻辑坐标），只在关卡进行中计入
+    pub fn record_pointer(&mut self, sample: PointerSample) {
+        if !self.is_stage_active() {
+            return;
+        }
+        self.tracking.record_pointer(
+            self.stage_elapsed,
+            sample,
+            self.response_target(),
+            self.ball.radius,
+        );
+    }
+
+    /// 跟踪评分使用的目标位置：反扫视关卡为提示的水平镜像，其余关卡为小球位置
+    pub fn response_target(&self) -> (f64, f64) {
+        match self.current_stage().motion_type {
+            MotionType::Saccade { rule: JumpRule::AntiSaccade, .. } => {
+                (self.ball.screen_w - self.ball.x, self.ball.y)
+            }
+            _ => (self.ball.x, self.ball.y),
+        }
+    }
+
+    /// 记录一个眼动采样，`space` 为眼动仪使用的坐标系
+    pub fn record_gaze(&mut self, sample: GazeSample, space: GazeSpace) {
+        if !self.is_stage_active() {
+            return;
+        }
+        let (x, y) = space.to_logical(sample.x, sample.y, self.ball.screen_w, self.ball.screen_h);
+        self.gaze.record(self.stage_elapsed, sample.t, x, y);
+    }
+
+    /// 执行一个物理步
+    pub fn step(&mut self, dt: f64, events: &mut Vec<GameEvent>) -> Result<()> {
         if self.is_game_over || self.is_start_screen || self.paused {
-            return Ok(GameUpdate {
-                events,
-                time_elapsed: self.stage_elapsed,
-            });
+            return Ok(());
         }
 
         if self.is_transitioning {
@@ -52,118 +219,158 @@ impl GameState {
                 self.is_transitioning = false;
                 self.stage_elapsed = 0.0;
             }
-            return Ok(GameUpdate {
-                events,
-                time_elapsed: self.stage_elapsed,
-            });
+            return Ok(());
         }
 
+        let prev_elapsed = self.stage_elapsed;
         self.stage_elapsed += dt;
 
-        if self.stage == 5 {
-            if self.stage5_paused {
-                self.stage5_pause_elapsed += dt;
-                if self.stage5_pause_elapsed >= 0.3 {
-                    self.stage5_paused = false;
-                }
-            } else {
-                // Stage 5 小球速度
-                let ang_spd = if self.stage_elapsed < 22.5 { 1.2 } else { -1.2 };
-                
-                // 修正 Stage 5 边界：距离边界 16px (相对)
-                let r = self.ball.radius;
-                let margin = 16.0;
-                let orbit_radius = (self.ball.screen_h - 2.0 * (r + margin)) / 2.0;
-                self.ball.update_circular_with_radius(dt, ang_spd, orbit_radius);
-                
-                // 边界检查确保不越界
-                let min_x = r + margin;
-                let max_x = self.ball.screen_w - (r + margin);
-                let min_y = r + margin;
-                let max_y = self.ball.screen_h - (r + margin);
-                
-                if self.ball.y < min_y { self.ball.y = min_y; }
-                else if self.ball.y > max_y { self.ball.y = max_y; }
-                if self.ball.x < min_x { self.ball.x = min_x; }
-                else if self.ball.x > max_x { self.ball.x = max_x; }
-
-                if self.stage_elapsed >= 22.5 && self.stage_elapsed < 22.6 {
-                    self.stage5_paused = true;
-                    self.stage5_pause_elapsed = 0.0;
+        let config = self.current_stage();
+        let (motion_type, speed, duration, depth, ball_collisions) = (
+            config.motion_type,
+            config.speed,
+            config.duration,
+            config.depth,
+            config.ball_collisions,
+        );
+
+        if let Some(depth) = depth {
+            self.ball.set_scale(depth.scale_at(self.stage_elapsed, duration));
+        }
+
+        match motion_type {
+            MotionType::Circular { angular_speed, reverse_at } => {
+                let reverse_time = reverse_at.resolve(duration);
+                if self.orbit_paused {
+                    self.orbit_pause_elapsed += dt;
+                    if self.orbit_pause_elapsed >= ORBIT_REVERSE_PAUSE {
+                        self.orbit_paused = false;
+                    }
+                } else {
+                    let ang_spd = if self.stage_elapsed < reverse_time { angular_speed } else { -angular_speed };
+
+                    let orbit_radius = self.max_orbit_radius();
+                    self.ball.update_circular_with_radius(dt, ang_spd, orbit_radius);
+                    self.clamp_to_orbit_bounds();
+
+                    // 跨过反向时间点的那一帧进入短暂停顿
+                    if prev_elapsed < reverse_time && self.stage_elapsed >= reverse_time {
+                        self.orbit_paused = true;
+                        self.orbit_pause_elapsed = 0.0;
+                    }
                 }
             }
-        } else if self.stage == 4 {
-            // Stage 4: 遵循“碰到边界弹开”的原则
-            if self.ball.update(dt)? {
-                events.push(GameEvent::BallBounced);
-                // 为了让运动更“无规律一些”，我们在反弹时加入随机角度偏移
-                let mut rng = rand::thread_rng();
-                let nudge = rng.gen_range(-0.1..0.1); // 增加偏移范围到约 +/- 6 度
-                let current_speed = (self.ball.vx * self.ball.vx + self.ball.vy * self.ball.vy).sqrt();
-                let current_angle = self.ball.vy.atan2(self.ball.vx);
-                let new_angle = current_angle + nudge;
-                self.ball.vx = new_angle.cos() * current_speed;
-                self.ball.vy = new_angle.sin() * current_speed;
+            MotionType::Spiral { angular_speed, radial_speed } => {
+                // 轨道半径以 radial_speed 在中心与最大半径之间往返（三角波）
+                let max_radius = self.max_orbit_radius();
+                let orbit_radius = if max_radius > 0.0 {
+                    let travelled = (radial_speed * self.stage_elapsed) % (2.0 * max_radius);
+                    if travelled <= max_radius { travelled } else { 2.0 * max_radius - travelled }
+                } else {
+                    0.0
+                };
+                self.ball.update_circular_with_radius(dt, angular_speed, orbit_radius);
+                self.clamp_to_orbit_bounds();
             }
-        } else if self.stage == 3 {
-            // Stage 3: 前 22.5s 水平居中运动，后 22.5s 垂直居中运动
-            let is_first_half = self.stage_elapsed < 22.5;
-            let base_speed = stage_speed(3);
-            
-            if is_first_half {
-                // 水平运动：标准速度 (x1.0)，位置强制居中
-                let h_speed = base_speed;
-                self.ball.vy = 0.0;
-                self.ball.y = self.ball.screen_h / 2.0;
-                self.ball.vx = if self.ball.vx >= 0.0 { h_speed } else { -h_speed };
-            } else {
-                // 垂直运动：速度为水平速度的 0.7 倍 (x0.7)，位置强制居中
-                let v_speed = base_speed * 0.7;
-                self.ball.vx = 0.0;
-                self.ball.x = self.ball.screen_w / 2.0;
-                self.ball.vy = if self.ball.vy >= 0.0 { v_speed } else { -v_speed };
+            MotionType::Saccade { dwell, rule, amplitude } => {
+                self.saccade_timer += dt;
+                if self.saccade_timer >= dwell {
+                    self.saccade_timer -= dwell;
+                    self.saccade_jumps += 1;
+                    let from = (self.ball.x, self.ball.y);
+                    let to = self.saccade_target(rule, amplitude, self.saccade_jumps);
+                    self.ball.x = to.0;
+                    self.ball.y = to.1;
+                    // 跳视是离散跳变，渲染端不应在两点之间插值
+                    self.interpolation.prev_x = to.0;
+                    self.interpolation.prev_y = to.1;
+                    events.push(GameEvent::TargetJumped {
+                        from,
+                        to,
+                        response: self.response_target(),
+                        stage_time: self.stage_elapsed,
+                    });
+                }
             }
-
-            if self.ball.update(dt)? {
-                events.push(GameEvent::BallBounced);
+            MotionType::Lissajous { freq_x, freq_y, phase, amplitude } => {
+                self.ball
+                    .update_lissajous(self.stage_elapsed, freq_x, freq_y, phase, amplitude);
             }
-        } else if self.stage == 2 {
-            // Stage 2: 仅保留垂直运动
-            if self.ball.update(dt)? {
-                events.push(GameEvent::BallBounced);
+            MotionType::CenterLines { vertical_speed_ratio, switch_at } => {
+                if self.stage_elapsed < switch_at.resolve(duration) {
+                    // 水平运动：标准速度 (x1.0)，位置强制居中
+                    let h_speed = speed;
+                    self.ball.vy = 0.0;
+                    self.ball.y = self.ball.screen_h / 2.0;
+                    self.ball.vx = if self.ball.vx >= 0.0 { h_speed } else { -h_speed };
+                } else {
+                    // 垂直运动：速度为水平速度的 vertical_speed_ratio 倍，位置强制居中
+                    let v_speed = speed * vertical_speed_ratio;
+                    self.ball.vx = 0.0;
+                    self.ball.x = self.ball.screen_w / 2.0;
+                    self.ball.vy = if self.ball.vy >= 0.0 { v_speed } else { -v_speed };
+                }
+
+                let bounces = self.ball.update(dt)?;
+                push_bounces(events, TARGET_BALL, &bounces, prev_elapsed);
             }
-        } else {
-            // Stage 1: 标准碰撞反弹
-            if self.ball.update(dt)? {
-                events.push(GameEvent::BallBounced);
+            MotionType::Linear { bounce_jitter, .. } => {
+                let bounces = self.ball.update(dt)?;
+                push_bounces(events, TARGET_BALL, &bounces, prev_elapsed);
+                if !bounces.is_empty() && bounce_jitter > 0.0 {
+                    // 为了让运动更“无规律一些”，在反弹时加入随机角度偏移
+                    let nudge = self.rng.gen_range(-bounce_jitter..bounce_jitter);
+                    let current_speed = (self.ball.vx * self.ball.vx + self.ball.vy * self.ball.vy).sqrt();
+                    let current_angle = self.ball.vy.atan2(self.ball.vx);
+                    let new_angle = current_angle + nudge;
+                    self.ball.vx = new_angle.cos() * current_speed;
+                    self.ball.vy = new_angle.sin() * current_speed;
+                }
             }
         }
 
-        if (self.stage_elapsed * 1000.0) as u64 > STAGE_DURATIONS_MS {
-            self.next_stage(&mut events);
+        for (index, distractor) in self.distractors.iter_mut().enumerate() {
+            let bounces = distractor.advance(dt, self.stage_elapsed)?;
+            push_bounces(events, index + 1, &bounces, prev_elapsed);
         }
 
-        Ok(GameUpdate {
-            events,
-            time_elapsed: self.stage_elapsed,
-        })
+        if ball_collisions {
+            let target_free = matches!(motion_type, MotionType::Linear { .. });
+            self.resolve_ball_collisions(target_free, events);
+        }
+
+        let (target_x, target_y) = self.response_target();
+        self.trajectory.record(self.stage_elapsed, target_x, target_y);
+
+        if self.stage_elapsed > duration {
+            events.push(GameEvent::StageCompleted {
+                stage: self.stage,
+                tracking: self.tracking.summary(&self.trajectory),
+                report: Box::new(self.stage_report()),
+            });
+            self.next_stage(events);
+        }
+
+        Ok(())
     }
 
     pub fn next_stage(&mut self, events: &mut Vec<GameEvent>) {
-        if self.stage >= 5 {
+        if self.stage >= self.stage_count() {
             self.is_game_over = true;
             events.push(GameEvent::GameOver);
         } else {
             let prev_stage = self.stage;
             self.stage += 1;
             self.is_transitioning = true;
-            self.transition_timer = 3.0; // 关卡间倒计时维持 3.0s
-            
+            self.transition_timer = self.program.transition_duration;
+
             // 每一关开始时，将小球重置到随机位置，打破起始点与上一关终点的联系
-            self.ball.reset_to_random_pos(self.ball.screen_w, self.ball.screen_h);
-            
+            // 先换成新关卡的景深比例，半径才与新关卡一致
+            self.ball.set_scale(self.stage_start_scale());
             self.ball
-                .set_speed(stage_speed(self.stage), Some(stage_direction(self.stage)));
+                .reset_to_random_pos(self.ball.screen_w, self.ball.screen_h, &mut self.rng);
+
+            self.apply_stage_motion();
             events.push(GameEvent::StageChanged {
                 from: prev_stage,
                 to: self.stage,
@@ -176,13 +383,15 @@ impl GameState {
             let prev_stage = self.stage;
             self.stage -= 1;
             self.is_transitioning = true;
-            self.transition_timer = 3.0;
-            
+            self.transition_timer = self.program.transition_duration;
+
             // 切换回上一关时也随机重置位置
-            self.ball.reset_to_random_pos(self.ball.screen_w, self.ball.screen_h);
-            
+            // 先换成新关卡的景深比例，半径才与新关卡一致
+            self.ball.set_scale(self.stage_start_scale());
             self.ball
-                .set_speed(stage_speed(self.stage), Some(stage_direction(self.stage)));
+                .reset_to_random_pos(self.ball.screen_w, self.ball.screen_h, &mut self.rng);
+
+            self.apply_stage_motion();
             events.push(GameEvent::StageChanged {
                 from: prev_stage,
                 to: self.stage,
@@ -190,78 +399,219 @@ impl GameState {
         }
     }
 
+    /// 重新开始训练，并使用新的随机种子
     pub fn reset(&mut self, w: f64, h: f64) {
+        self.reset_with_seed(w, h, random_seed());
+    }
+
+    pub fn reset_with_seed(&mut self, w: f64, h: f64, seed: u64) {
+        self.seed = seed;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
         self.stage = 1;
         self.stage_elapsed = 0.0;
         self.is_game_over = false;
         self.is_transitioning = true;
-        self.transition_timer = 3.0; // 重置也统一为 3.0s
+        self.transition_timer = self.program.transition_duration;
         self.paused = false;
+        self.accumulator = 0.0;
         self.ball.reset(w, h);
-        self.ball.set_speed(stage_speed(1), Some(stage_direction(1)));
+        self.apply_stage_motion();
+    }
+
+    /// 回到开始界面，从第 `stage` 关开始（超出范围时取最近的关卡），用于恢复用户档案的进度
+    pub fn reset_to_stage(&mut self, stage: i32) {
+        self.reset(self.ball.screen_w, self.ball.screen_h);
+        self.stage = stage.clamp(1, self.stage_count());
+        self.is_start_screen = true;
+        self.apply_stage_motion();
     }
 
     pub fn resize(&mut self, w: f64, h: f64) {
         self.ball.update_screen_size(w, h);
+        for distractor in &mut self.distractors {
+            distractor.ball.update_screen_size(w, h);
+        }
     }
-}
 
-pub fn stage_direction(stage: i32) -> (f64, f64) {
-    let mut rng = rand::thread_rng();
-    match stage {
-        1 | 5 => {
-            let angle_deg = rng.gen_range(10.0..20.0);
-            let angle_rad = angle_deg * PI / 180.0;
-            if rng.gen_bool(0.5) {
-                (angle_rad.cos(), angle_rad.sin())
-            } else {
-                (-angle_rad.cos(), angle_rad.sin())
-            }
+    fn apply_stage_motion(&mut self) {
+        self.trajectory.clear();
+        self.tracking.reset();
+        self.gaze.reset();
+        self.orbit_paused = false;
+        self.orbit_pause_elapsed = 0.0;
+        self.saccade_timer = 0.0;
+        self.saccade_jumps = 0;
+        self.ball.set_scale(self.stage_start_scale());
+        if let MotionType::Saccade { rule, amplitude, .. } = self.current_stage().motion_type {
+            let (x, y) = self.saccade_target(rule, amplitude, 0);
+            self.ball.x = x;
+            self.ball.y = y;
         }
-        2 => {
-            // Stage 2: 垂直方向带有一点随机偏角 (7-17度)
-            let angle_deg = rng.gen_range(7.0..17.0);
-            let angle_rad = angle_deg * PI / 180.0;
-            let vx = if rng.gen_bool(0.5) { angle_rad.sin() } else { -angle_rad.sin() };
-            let vy = if rng.gen_bool(0.5) { angle_rad.cos() } else { -angle_rad.cos() };
-            (vx, vy)
-        }
-        4 => {
-            // 为 Stage 4 提供更随机的初始角度，不再仅限于垂直附近
-            let angle_deg = rng.gen_range(20.0..70.0); // 20-70度，更像对角线
-            let angle_rad = angle_deg * PI / 180.0;
-            if rng.gen_bool(0.5) {
-                (angle_rad.sin(), angle_rad.cos())
-            } else {
-                (angle_rad.sin(), -angle_rad.cos())
+
+        let (w, h) = (self.ball.screen_w, self.ball.screen_h);
+        let motions = self.program.stages[self.stage_index()].distractors.clone();
+        self.distractors = motions
+            .into_iter()
+            .map(|motion| Distractor::spawn(w, h, motion, &mut self.rng))
+            .collect();
+        self.interpolation = Interpolation {
+            prev_x: self.ball.x,
+            prev_y: self.ball.y,
+            alpha: 0.0,
+        };
+        let config = &self.program.stages[self.stage_index()];
+        let direction = config.initial_direction(&mut self.rng);
+        self.ball.set_speed(config.speed, Some(direction));
+    }
+
+    /// 当前关卡开始时的景深比例
+    fn stage_start_scale(&self) -> f64 {
+        let config = self.current_stage();
+        config.depth.map_or(1.0, |d| d.scale_at(0.0, config.duration))
+    }
+
+    /// 圆周运动的最大轨道半径：距离上下边界 16px (相对)
+    fn max_orbit_radius(&self) -> f64 {
+        ((self.ball.screen_h - 2.0 * (self.ball.radius + EDGE_MARGIN)) / 2.0).max(0.0)
+    }
+
+    /// 边界检查确保圆周运动不越界
+    fn clamp_to_orbit_bounds(&mut self) {
+        let r = self.ball.radius;
+        let min_x = r + EDGE_MARGIN;
+        let max_x = self.ball.screen_w - (r + EDGE_MARGIN);
+        let min_y = r + EDGE_MARGIN;
+        let max_y = self.ball.screen_h - (r + EDGE_MARGIN);
+
+        if self.ball.y < min_y { self.ball.y = min_y; }
+        else if self.ball.y > max_y { self.ball.y = max_y; }
+        if self.ball.x < min_x { self.ball.x = min_x; }
+        else if self.ball.x > max_x { self.ball.x = max_x; }
+    }
+
+    /// 小球之间的弹性碰撞（等质量）。只有自由运动的小球会改变速度，
+    /// 按轨迹运动的小球视为不可推动的障碍
+    fn resolve_ball_collisions(&mut self, target_free: bool, events: &mut Vec<GameEvent>) {
+        let mut balls: Vec<(&mut Ball, bool)> = std::iter::once((&mut self.ball, target_free))
+            .chain(self.distractors.iter_mut().map(|d| {
+                let free = d.is_free();
+                (&mut d.ball, free)
+            }))
+            .collect();
+
+        for i in 0..balls.len() {
+            let (head, tail) = balls.split_at_mut(i + 1);
+            let (a, a_free) = (&mut head[i].0, head[i].1);
+            for (j, (b, b_free)) in tail.iter_mut().enumerate() {
+                let (dx, dy) = (b.x - a.x, b.y - a.y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                let min_distance = a.radius + b.radius;
+                if distance >= min_distance || distance == 0.0 || !(a_free || *b_free) {
+                    continue;
+                }
+                let (nx, ny) = (dx / distance, dy / distance);
+
+                // 沿法线分开重叠部分
+                let overlap = min_distance - distance;
+                let (a_share, b_share) = match (a_free, *b_free) {
+                    (true, true) => (0.5, 0.5),
+                    (true, false) => (1.0, 0.0),
+                    _ => (0.0, 1.0),
+                };
+                a.x -= nx * overlap * a_share;
+                a.y -= ny * overlap * a_share;
+                b.x += nx * overlap * b_share;
+                b.y += ny * overlap * b_share;
+
+                // 只处理相互靠近的情况
+                let approach = (a.vx - b.vx) * nx + (a.vy - b.vy) * ny;
+                if approach <= 0.0 {
+                    continue;
+                }
+                match (a_free, *b_free) {
+                    (true, true) => {
+                        a.vx -= approach * nx;
+                        a.vy -= approach * ny;
+                        b.vx += approach * nx;
+                        b.vy += approach * ny;
+                    }
+                    (true, false) => {
+                        let along = a.vx * nx + a.vy * ny;
+                        a.vx -= 2.0 * along * nx;
+                        a.vy -= 2.0 * along * ny;
+                    }
+                    _ => {
+                        let along = b.vx * nx + b.vy * ny;
+                        b.vx -= 2.0 * along * nx;
+                        b.vy -= 2.0 * along * ny;
+                    }
+                }
+
+                events.push(GameEvent::BallsCollided {
+                    a: i,
+                    b: i + 1 + j,
+                    x: a.x + nx * a.radius,
+                    y: a.y + ny * a.radius,
+                    stage_time: self.stage_elapsed,
+                });
             }
         }
-        3 => {
-            // Stage 3 初始强制水平向左或向右
-            if rng.gen_bool(0.5) {
-                (1.0, 0.0)
-            } else {
-                (-1.0, 0.0)
+    }
+
+    /// 第 `index` 个扫视目标的位置
+    fn saccade_target(&mut self, rule: JumpRule, amplitude: f64, index: u32) -> (f64, f64) {
+        let center_x = self.ball.screen_w / 2.0;
+        let center_y = self.ball.screen_h / 2.0;
+        let reach_x = amplitude * (center_x - self.ball.radius - EDGE_MARGIN).max(0.0);
+        let reach_y = amplitude * (center_y - self.ball.radius - EDGE_MARGIN).max(0.0);
+
+        match rule {
+            JumpRule::Random => (
+                center_x + reach_x * self.rng.gen_range(-1.0..=1.0),
+                center_y + reach_y * self.rng.gen_range(-1.0..=1.0),
+            ),
+            JumpRule::Alternating => {
+                let side = if index.is_multiple_of(2) { -1.0 } else { 1.0 };
+                (center_x + side * reach_x, center_y)
+            }
+            JumpRule::Grid { columns, rows } => {
+                let cell = index % (columns * rows);
+                let (col, row) = (cell % columns, cell / columns);
+                // 单列/单行时位于中线，否则均匀分布在 [-reach, reach]
+                let spread = |i: u32, n: u32| if n > 1 { 2.0 * i as f64 / (n - 1) as f64 - 1.0 } else { 0.0 };
+                (
+                    center_x + reach_x * spread(col, columns),
+                    center_y + reach_y * spread(row, rows),
+                )
+            }
+            JumpRule::AntiSaccade => {
+                let side = if self.rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+                (center_x + side * reach_x, center_y)
             }
         }
-        _ => (1.0, 1.0),
     }
 }
 
-pub fn stage_speed(stage: i32) -> f64 {
-    match stage {
-        1 => 1000.0,
-        2 => 1100.0, // 从 1250.0 降低到 1100.0
-        3 => 1625.0,
-        4 => 1500.0, // 从 1800.0 降低到 1500.0
-        5 => 0.0,
-        _ => 1000.0,
-    }
+fn push_bounces(events: &mut Vec<GameEvent>, ball: usize, bounces: &[Bounce], step_start: f64) {
+    events.extend(bounces.iter().map(|b| GameEvent::BallBounced {
+        ball,
+        x: b.x,
+        y: b.y,
+        wall: b.wall,
+        stage_time: step_start + b.time,
+    }));
+}
+
+/// 生成新的随机种子，限制在 53 位以内以便前端 (JS number) 无损显示
+fn random_seed() -> u64 {
+    rand::thread_rng().gen_range(0..(1u64 << 53))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stage_config::{MAX_DEPTH_SCALE, MAX_GRID_CELLS};
+    use crate::{DepthCurve, DistractorMotion, PhasePoint, Wall};
 
     #[test]
     fn test_game_state_creation() {
@@ -316,4 +666,661 @@ mod tests {
         assert_eq!(state.ball.screen_w, 1000.0);
         assert_eq!(state.ball.screen_h, 800.0);
     }
+
+    #[test]
+    fn test_reset_to_stage() {
+        let mut state = GameState::new(1920.0, 1080.0);
+        state.is_start_screen = false;
+        let mut events = Vec::new();
+        state.next_stage(&mut events);
+
+        state.reset_to_stage(4);
+        assert_eq!(state.stage, 4);
+        assert!(state.is_start_screen && state.is_transitioning);
+        state.reset_to_stage(99);
+        assert_eq!(state.stage, state.stage_count());
+        state.reset_to_stage(0);
+        assert_eq!(state.stage, 1);
+    }
+
+    #[test]
+    fn test_custom_program() {
+        let program = TrainingProgram {
+            name: "custom".to_string(),
+            transition_duration: 1.0,
+            stages: vec![
+                StageConfig {
+                    duration: 10.0,
+                    ..StageConfig::default()
+                },
+                StageConfig {
+                    speed: 500.0,
+                    duration: 5.0,
+                    ..StageConfig::default()
+                },
+            ],
+        };
+        let mut state = GameState::with_program(800.0, 600.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        state.stage_elapsed = 9.5;
+
+        let mut events = Vec::new();
+        state.step(1.0, &mut events).unwrap();
+        assert!(events.contains(&GameEvent::StageChanged { from: 1, to: 2 }));
+        let speed = (state.ball.vx * state.ball.vx + state.ball.vy * state.ball.vy).sqrt();
+        assert!((speed - 500.0).abs() < 1e-9);
+        assert_eq!(state.transition_timer, 1.0);
+
+        state.is_transitioning = false;
+        state.stage_elapsed = 5.5;
+        state.step(0.1, &mut events).unwrap();
+        assert!(state.is_game_over);
+        assert!(events.contains(&GameEvent::GameOver));
+    }
+
+    #[test]
+    fn test_phase_point_in_seconds() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 1000.0,
+                duration: 20.0,
+                motion_type: MotionType::CenterLines {
+                    vertical_speed_ratio: 0.5,
+                    switch_at: PhasePoint::Seconds(5.0),
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(800.0, 600.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        state.stage_elapsed = 4.0;
+        state.update(0.01).unwrap();
+        assert_eq!(state.ball.vy, 0.0);
+        assert_eq!(state.ball.vx.abs(), 1000.0);
+
+        state.stage_elapsed = 5.0;
+        state.update(0.01).unwrap();
+        assert_eq!(state.ball.vx, 0.0);
+        assert_eq!(state.ball.vy.abs(), 500.0);
+    }
+
+    #[test]
+    fn test_orbit_pauses_once_at_reverse_point() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 10.0,
+                motion_type: MotionType::Circular {
+                    angular_speed: 1.0,
+                    reverse_at: PhasePoint::Fraction(0.25),
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(800.0, 600.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        state.stage_elapsed = 2.4;
+        let mut events = Vec::new();
+        state.step(0.2, &mut events).unwrap();
+        assert!(state.orbit_paused);
+
+        state.step(0.5, &mut events).unwrap();
+        assert!(!state.orbit_paused);
+        state.step(0.5, &mut events).unwrap();
+        assert!(!state.orbit_paused);
+    }
+
+    #[test]
+    fn test_lissajous_stage() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 10.0,
+                motion_type: MotionType::Lissajous {
+                    freq_x: 0.2,
+                    freq_y: 0.4,
+                    phase: 0.0,
+                    amplitude: 0.8,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(1920.0, 1080.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        let mut events = Vec::new();
+        state.step(1.25, &mut events).unwrap();
+        // 四分之一个 x 周期：x 到达右侧振幅顶点，y 回到中线
+        let ax = 0.8 * (960.0 - state.ball.radius);
+        assert!((state.ball.x - (960.0 + ax)).abs() < 1e-6);
+        assert!((state.ball.y - 540.0).abs() < 1e-6);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_saccade_alternating_jumps() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 10.0,
+                motion_type: MotionType::Saccade {
+                    dwell: 1.0,
+                    rule: JumpRule::Alternating,
+                    amplitude: 1.0,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(1920.0, 1080.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        let left = state.ball.radius + EDGE_MARGIN;
+        assert_eq!((state.ball.x, state.ball.y), (left, 540.0));
+
+        let mut events = Vec::new();
+        state.step(0.5, &mut events).unwrap();
+        assert!(events.is_empty());
+
+        state.step(0.5, &mut events).unwrap();
+        assert_eq!(
+            events,
+            vec![GameEvent::TargetJumped {
+                from: (left, 540.0),
+                to: (1920.0 - left, 540.0),
+                response: (1920.0 - left, 540.0),
+                stage_time: 1.0,
+            }]
+        );
+        assert_eq!(state.ball.x, 1920.0 - left);
+    }
+
+    #[test]
+    fn test_saccade_jump_is_not_interpolated() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 10.0,
+                motion_type: MotionType::Saccade {
+                    dwell: 0.5,
+                    rule: JumpRule::Alternating,
+                    amplitude: 1.0,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(1920.0, 1080.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        let mut jumped = false;
+        while !jumped {
+            let update = state.update(state.fixed_dt * 1.5).unwrap();
+            jumped = update.events.iter().any(|e| matches!(e, GameEvent::TargetJumped { .. }));
+        }
+        assert_eq!(
+            (state.interpolation.prev_x, state.interpolation.prev_y),
+            (state.ball.x, state.ball.y)
+        );
+    }
+
+    #[test]
+    fn test_saccade_grid_cycles() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 30.0,
+                motion_type: MotionType::Saccade {
+                    dwell: 1.0,
+                    rule: JumpRule::Grid { columns: 3, rows: 2 },
+                    amplitude: 0.5,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(1920.0, 1080.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        let start = (state.ball.x, state.ball.y);
+        let mut events = Vec::new();
+        for _ in 0..6 {
+            state.step(1.0, &mut events).unwrap();
+        }
+        assert_eq!(events.len(), 6);
+        assert_eq!((state.ball.x, state.ball.y), start);
+        // 第二个目标位于同一行的中列
+        if let GameEvent::TargetJumped { to, .. } = events[0] {
+            assert_eq!(to, (960.0, start.1));
+        } else {
+            panic!("expected TargetJumped");
+        }
+    }
+
+    #[test]
+    fn test_anti_saccade_scores_against_mirrored_target() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 5.0,
+                motion_type: MotionType::Saccade {
+                    dwell: 0.5,
+                    rule: JumpRule::AntiSaccade,
+                    amplitude: 0.8,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_seed(1920.0, 1080.0, program, 5).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        let mut events = Vec::new();
+        while !state.is_game_over {
+            let (x, y) = state.response_target();
+            state.record_pointer(PointerSample { x, y });
+            state.step(1.0 / 60.0, &mut events).unwrap();
+        }
+
+        let center_x = 1920.0 / 2.0;
+        let mut jumps = 0;
+        for event in &events {
+            if let GameEvent::TargetJumped { to, response, .. } = *event {
+                assert_eq!(response, (2.0 * center_x - to.0, to.1));
+                assert_ne!(response, to);
+                jumps += 1;
+            }
+        }
+        assert!(jumps >= 9, "{}", jumps);
+
+        // 一直注视镜像位置应判定为跟上目标
+        let summary = events
+            .iter()
+            .find_map(|e| match e {
+                GameEvent::StageCompleted { tracking, .. } => tracking.clone(),
+                _ => None,
+            })
+            .expect("pointer summary");
+        assert!(summary.score > 90.0, "{}", summary.score);
+    }
+
+    #[test]
+    fn test_saccade_grid_stays_inside_margin() {
+        let grid = |columns: u32, rows: u32, amplitude: f64| TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                motion_type: MotionType::Saccade {
+                    dwell: 1.0,
+                    rule: JumpRule::Grid { columns, rows },
+                    amplitude,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+
+        // 单格网格始终位于屏幕中心
+        let mut state = GameState::with_program(1920.0, 1080.0, grid(1, 1, 1.0)).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            assert_eq!((state.ball.x, state.ball.y), (960.0, 540.0));
+            state.step(1.0, &mut events).unwrap();
+        }
+        assert_eq!(events.len(), 3);
+
+        // 满幅时四角正好落在留白边缘
+        let mut state = GameState::with_program(1920.0, 1080.0, grid(2, 2, 1.0)).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        let edge = state.ball.radius + EDGE_MARGIN;
+        let mut corners = vec![(state.ball.x, state.ball.y)];
+        for _ in 0..3 {
+            state.step(1.0, &mut events).unwrap();
+            corners.push((state.ball.x, state.ball.y));
+        }
+        assert_eq!(
+            corners,
+            [(edge, edge), (1920.0 - edge, edge), (edge, 1080.0 - edge), (1920.0 - edge, 1080.0 - edge)]
+        );
+
+        assert!(grid(2, 2, 1.01).validate().is_err());
+    }
+
+    #[test]
+    fn test_saccade_grid_size_is_validated() {
+        let grid = |columns: u32, rows: u32| StageConfig {
+            motion_type: MotionType::Saccade {
+                dwell: 1.0,
+                rule: JumpRule::Grid { columns, rows },
+                amplitude: 0.5,
+            },
+            ..StageConfig::default()
+        };
+        assert!(grid(32, 32).validate().is_ok());
+        assert!(grid(0, 3).validate().is_err());
+        assert!(grid(MAX_GRID_CELLS + 1, 1).validate().is_err());
+        assert_eq!(grid(70_000, 70_000).validate(), Err("motion_type.rule".to_string()));
+        assert_eq!(grid(u32::MAX, 2).validate(), Err("motion_type.rule".to_string()));
+    }
+
+    #[test]
+    fn test_spiral_radius_grows_and_returns() {
+        let program = TrainingProgram {
+            stages: vec![StageConfig {
+                speed: 0.0,
+                duration: 60.0,
+                motion_type: MotionType::Spiral {
+                    angular_speed: 2.0,
+                    radial_speed: 100.0,
+                },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(1920.0, 1080.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        state.ball.reset(1920.0, 1080.0);
+        let max_radius = state.max_orbit_radius();
+        let distance = |s: &GameState| (s.ball.x - 960.0).hypot(s.ball.y - 540.0);
+
+        let mut events = Vec::new();
+        let steps_to_edge = (max_radius / 100.0 / state.fixed_dt).round() as usize;
+        let mut max_seen: f64 = 0.0;
+        for _ in 0..steps_to_edge {
+            state.step(state.fixed_dt, &mut events).unwrap();
+            max_seen = max_seen.max(distance(&state));
+        }
+        assert!((distance(&state) - max_radius).abs() < 1.0);
+        assert!(max_seen <= max_radius + 1e-6);
+
+        for _ in 0..steps_to_edge {
+            state.step(state.fixed_dt, &mut events).unwrap();
+        }
+        assert!(distance(&state) < 1.0);
+    }
+
+    #[test]
+    fn test_depth_curve_scales_ball() {
+        let program = TrainingProgram {
+            stages: vec![
+                StageConfig {
+                    duration: 10.0,
+                    depth: Some(DepthCurve::Ramp { from: 1.0, to: 0.5 }),
+                    ..StageConfig::default()
+                },
+                StageConfig::default(),
+            ],
+            ..TrainingProgram::default()
+        };
+        let mut state = GameState::with_program(1920.0, 1080.0, program).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        let base_radius = state.ball.radius;
+
+        let mut events = Vec::new();
+        state.step(5.0, &mut events).unwrap();
+        assert_eq!(state.ball.scale, 0.75);
+        assert_eq!(state.ball.radius, base_radius * 0.75);
+
+        // 下一关没有景深通道，恢复原始大小
+        state.next_stage(&mut events);
+        assert_eq!(state.ball.scale, 1.0);
+        assert_eq!(state.ball.radius, base_radius);
+    }
+
+    #[test]
+    fn test_stage_start_position_uses_new_depth_scale() {
+        let program = TrainingProgram {
+            stages: vec![
+                StageConfig { depth: Some(DepthCurve::Ramp { from: 0.5, to: 0.5 }), ..StageConfig::default() },
+                StageConfig { depth: Some(DepthCurve::Ramp { from: 5.0, to: 5.0 }), ..StageConfig::default() },
+            ],
+            ..TrainingProgram::default()
+        };
+        for seed in 0..20 {
+            let mut state = GameState::with_program(1920.0, 1080.0, program.clone()).unwrap();
+            state.reset_with_seed(1920.0, 1080.0, seed);
+            let mut events = Vec::new();
+            state.next_stage(&mut events);
+            let r = state.ball.radius;
+            assert_eq!(r, 1080.0 / 22.5 * 5.0);
+            assert!(state.ball.y >= r + 50.0 && state.ball.y <= 1080.0 - r - 50.0, "{}", state.ball.y);
+        }
+
+        let mut too_large = program.stages[1].clone();
+        too_large.depth = Some(DepthCurve::Ramp { from: 1.0, to: MAX_DEPTH_SCALE * 2.0 });
+        assert!(too_large.validate().is_err());
+    }
+
+    fn two_ball_program(ball_collisions: bool) -> TrainingProgram {
+        TrainingProgram {
+            stages: vec![
+                StageConfig {
+                    distractors: vec![DistractorMotion::Linear {
+                        speed: 500.0,
+                        angle_range: (0.0, 0.0),
+                    }],
+                    ball_collisions,
+                    ..StageConfig::default()
+                },
+                StageConfig::default(),
+            ],
+            ..TrainingProgram::default()
+        }
+    }
+
+    fn head_on(state: &mut GameState) {
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+        state.ball.x = 800.0;
+        state.ball.y = 540.0;
+        state.ball.vx = 500.0;
+        state.ball.vy = 0.0;
+        let other = &mut state.distractors[0].ball;
+        other.x = 1100.0;
+        other.y = 540.0;
+        other.vx = -500.0;
+        other.vy = 0.0;
+    }
+
+    #[test]
+    fn test_distractors_follow_stage() {
+        let mut state = GameState::with_program(1920.0, 1080.0, two_ball_program(false)).unwrap();
+        assert_eq!(state.distractors.len(), 1);
+        assert_eq!(state.distractors[0].ball.vx.abs(), 500.0);
+
+        let mut events = Vec::new();
+        state.next_stage(&mut events);
+        assert!(state.distractors.is_empty());
+    }
+
+    #[test]
+    fn test_distractor_bounce_reports_ball() {
+        let mut state = GameState::with_program(1920.0, 1080.0, two_ball_program(false)).unwrap();
+        head_on(&mut state);
+        state.ball.vx = 0.0;
+        state.distractors[0].ball.x = 100.0;
+
+        let mut events = Vec::new();
+        state.step(0.2, &mut events).unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [GameEvent::BallBounced { ball: 1, wall: Wall::Left, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_ball_collisions_exchange_velocity() {
+        let mut state = GameState::with_program(1920.0, 1080.0, two_ball_program(true)).unwrap();
+        head_on(&mut state);
+
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            state.step(state.fixed_dt, &mut events).unwrap();
+        }
+        assert!(events.iter().any(|e| matches!(e, GameEvent::BallsCollided { a: 0, b: 1, .. })));
+        assert_eq!(state.ball.vx, -500.0);
+        assert_eq!(state.distractors[0].ball.vx, 500.0);
+        assert!(state.distractors[0].ball.x - state.ball.x >= 2.0 * state.ball.radius - 1e-9);
+    }
+
+    #[test]
+    fn test_ball_collisions_disabled() {
+        let mut state = GameState::with_program(1920.0, 1080.0, two_ball_program(false)).unwrap();
+        head_on(&mut state);
+
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            state.step(state.fixed_dt, &mut events).unwrap();
+        }
+        assert!(events.is_empty());
+        assert_eq!(state.ball.vx, 500.0);
+    }
+
+    #[test]
+    fn test_same_seed_same_trajectory() {
+        let run = |seed: u64| {
+            let mut state = GameState::with_seed(800.0, 600.0, TrainingProgram::default(), seed).unwrap();
+            state.is_start_screen = false;
+            let mut trajectory = Vec::new();
+            for _ in 0..2000 {
+                state.update(1.0 / 60.0).unwrap();
+                trajectory.push((state.stage, state.ball.x, state.ball.y));
+            }
+            let mut events = Vec::new();
+            state.next_stage(&mut events);
+            state.next_stage(&mut events);
+            trajectory.push((state.stage, state.ball.x, state.ball.y));
+            trajectory
+        };
+
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+    }
+
+    #[test]
+    fn test_reset_with_seed_restarts_sequence() {
+        let mut state = GameState::with_seed(800.0, 600.0, TrainingProgram::default(), 7).unwrap();
+        let (vx, vy) = (state.ball.vx, state.ball.vy);
+        let mut events = Vec::new();
+        state.next_stage(&mut events);
+
+        state.reset_with_seed(800.0, 600.0, 7);
+        assert_eq!(state.seed, 7);
+        assert_eq!((state.ball.vx, state.ball.vy), (vx, vy));
+    }
+
+    #[test]
+    fn test_serialized_state_resumes_rng() {
+        let mut state = GameState::with_seed(800.0, 600.0, TrainingProgram::default(), 99).unwrap();
+        let mut events = Vec::new();
+        state.next_stage(&mut events);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let mut restored: GameState = serde_json::from_str(&json).unwrap();
+        state.next_stage(&mut events);
+        restored.next_stage(&mut events);
+        assert_eq!((state.ball.x, state.ball.vx), (restored.ball.x, restored.ball.vx));
+    }
+
+    #[test]
+    fn test_frame_rate_independent() {
+        let run = |fps: u32, seconds: u32| {
+            let mut state = GameState::with_seed(1920.0, 1080.0, TrainingProgram::default(), 5).unwrap();
+            state.is_start_screen = false;
+            let mut bounces = 0;
+            for _ in 0..fps * seconds {
+                let update = state.update(1.0 / fps as f64).unwrap();
+                bounces += update.events.len();
+            }
+            (state.stage, state.stage_elapsed, state.ball.x, state.ball.y, bounces)
+        };
+
+        assert_eq!(run(60, 10), run(540, 10));
+    }
+
+    #[test]
+    fn test_catch_up_is_capped() {
+        let mut state = GameState::new(800.0, 600.0);
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        state.update(5.0).unwrap();
+        let expected = state.fixed_dt * state.max_catch_up_steps as f64;
+        assert!((state.stage_elapsed - expected).abs() < 1e-9);
+        assert_eq!(state.accumulator, 0.0);
+    }
+
+    #[test]
+    fn test_interpolation_alpha() {
+        let mut state = GameState::new(800.0, 600.0);
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        state.update(state.fixed_dt * 1.5).unwrap();
+        assert!((state.interpolation.alpha - 0.5).abs() < 1e-6);
+        assert_ne!(state.interpolation.prev_x, state.ball.x);
+    }
+
+    #[test]
+    fn test_empty_program_rejected() {
+        let program = TrainingProgram {
+            stages: Vec::new(),
+            ..TrainingProgram::default()
+        };
+        assert!(GameState::with_program(800.0, 600.0, program).is_err());
+    }
+
+    #[test]
+    fn test_pointer_tracking_summary_on_stage_completed() {
+        let mut state = GameState::with_seed(800.0, 600.0, TrainingProgram::default(), 3).unwrap();
+        state.is_start_screen = false;
+        state.is_transitioning = false;
+
+        // 过渡期间的采样不计入
+        state.is_transitioning = true;
+        state.record_pointer(PointerSample { x: 0.0, y: 0.0 });
+        assert!(state.tracking.live_report(&state.trajectory).is_none());
+        state.is_transitioning = false;
+
+        let mut events = Vec::new();
+        let duration = state.current_stage().duration;
+        while state.stage == 1 {
+            state.record_pointer(PointerSample { x: state.ball.x, y: state.ball.y });
+            let gaze = GazeSample { t: None, x: state.ball.x / 800.0, y: state.ball.y / 600.0 };
+            state.record_gaze(gaze, GazeSpace::Normalized);
+            state.step(1.0 / 60.0, &mut events).unwrap();
+        }
+        let (summary, report) = events
+            .iter()
+            .find_map(|e| match e {
+                GameEvent::StageCompleted { stage: 1, tracking, report } => Some((tracking.clone(), report.as_ref().clone())),
+                _ => None,
+            })
+            .expect("stage 1 completed");
+        let summary = summary.expect("pointer summary");
+        assert!(report.pointer.is_some());
+        let pursuit = report.gaze.expect("gaze report");
+        assert!((pursuit.gain.unwrap() - 1.0).abs() < 1e-6);
+        assert_eq!(pursuit.catch_up_saccades, 0);
+        assert!(summary.score > 99.0);
+        assert!(summary.tracked_time > duration - 0.1);
+        // 进入下一关后重新统计
+        assert!(state.tracking.live_report(&state.trajectory).is_none());
+    }
 }
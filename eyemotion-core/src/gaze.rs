@@ -0,0 +1,359 @@
+use crate::analysis::TimedSample;
+use crate::{CoreError, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 默认监听端口
+pub const DEFAULT_GAZE_PORT: u16 = 4242;
+/// 监听线程检查停止标志的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 眼动仪发送的坐标所用的坐标系
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GazeSpace {
+    /// 0..1 的归一化屏幕坐标
+    #[default]
+    Normalized,
+    /// 眼动仪屏幕的像素坐标
+    Pixels { width: f64, height: f64 },
+}
+
+impl GazeSpace {
+    /// 转换为游戏逻辑坐标
+    pub fn to_logical(&self, x: f64, y: f64, screen_w: f64, screen_h: f64) -> (f64, f64) {
+        match *self {
+            GazeSpace::Normalized => (x * screen_w, y * screen_h),
+            GazeSpace::Pixels { width, height } => (x / width * screen_w, y / height * screen_h),
+        }
+    }
+
+    /// 像素坐标系的宽高必须为正数，否则换算出无穷大或 NaN
+    pub fn validate(&self) -> Result<()> {
+        if let GazeSpace::Pixels { width, height } = *self {
+            for (field, value) in [("width", width), ("height", height)] {
+                if !(value.is_finite() && value > 0.0) {
+                    return Err(CoreError::Config(format!("gaze: field `space.{}` must be positive", field)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum GazeProtocol {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+/// 眼动输入设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GazeConfig {
+    pub enabled: bool,
+    pub protocol: GazeProtocol,
+    pub port: u16,
+    pub space: GazeSpace,
+}
+
+impl GazeConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.space.validate()
+    }
+}
+
+impl Default for GazeConfig {
+    fn default() -> Self {
+        GazeConfig {
+            enabled: false,
+            protocol: GazeProtocol::Udp,
+            port: DEFAULT_GAZE_PORT,
+            space: GazeSpace::Normalized,
+        }
+    }
+}
+
+/// 一个眼动采样，协议为每行一个 JSON：`{"t": 12.345, "x": 0.52, "y": 0.41}`
+///
+/// `t` 为眼动仪自己的时钟（秒），省略时以收到的时刻为准
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GazeSample {
+    #[serde(default)]
+    pub t: Option<f64>,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl GazeSample {
+    pub fn parse_line(line: &str) -> Result<Self> {
+        let sample: GazeSample = serde_json::from_str(line)?;
+        if !sample.x.is_finite() || !sample.y.is_finite() || sample.t.is_some_and(|t| !t.is_finite()) {
+            return Err(CoreError::Config(format!("invalid gaze sample: {}", line)));
+        }
+        Ok(sample)
+    }
+}
+
+/// 将眼动采样对齐到关卡时间，供 `analysis` 计算跟踪指标
+#[derive(Debug, Clone, Default)]
+pub struct GazeTracker {
+    /// 关卡时间 = 眼动仪时间 + offset
+    offset: Option<f64>,
+    points: Vec<TimedSample>,
+}
+
+impl GazeTracker {
+    /// 开始新的关卡，清空采样并重新估计时钟偏移
+    pub fn reset(&mut self) {
+        *self = GazeTracker::default();
+    }
+
+    /// 记录在关卡时间 `arrival` 收到的采样（已转换为逻辑坐标）
+    pub fn record(&mut self, arrival: f64, tracker_time: Option<f64>, x: f64, y: f64) {
+        let t = match tracker_time {
+            Some(tracker_time) => {
+                // 采样总是在收到之前产生，取 arrival - t 的最小值作为时钟偏移，
+                // 暂停后关卡时钟落后，偏移会随之变小
+                let candidate = arrival - tracker_time;
+                let offset = self.offset.map_or(candidate, |o| o.min(candidate));
+                self.offset = Some(offset);
+                tracker_time + offset
+            }
+            None => arrival,
+        };
+        self.points.push(TimedSample { t, x, y });
+    }
+
+    /// 已对齐到关卡时间的采样（按收到顺序）
+    pub fn samples(&self) -> &[TimedSample] {
+        &self.points
+    }
+}
+
+/// 在本机端口上接收眼动仪数据的后台监听器，析构时停止
+pub struct GazeListener {
+    local_addr: SocketAddr,
+    queue: Arc<Mutex<Vec<GazeSample>>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl GazeListener {
+    /// 监听 `127.0.0.1:port`；端口为 0 时由系统分配
+    pub fn bind(protocol: GazeProtocol, port: u16) -> Result<Self> {
+        let queue = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+        let (local_addr, handle) = match protocol {
+            GazeProtocol::Udp => {
+                let socket = UdpSocket::bind(addr)?;
+                socket.set_read_timeout(Some(POLL_INTERVAL))?;
+                let local_addr = socket.local_addr()?;
+                let (queue, stop) = (queue.clone(), stop.clone());
+                (local_addr, thread::spawn(move || receive_udp(socket, queue, stop)))
+            }
+            GazeProtocol::Tcp => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                let local_addr = listener.local_addr()?;
+                let (queue, stop) = (queue.clone(), stop.clone());
+                (local_addr, thread::spawn(move || accept_tcp(listener, queue, stop)))
+            }
+        };
+
+        log::info!("Gaze listener ({:?}) on {}", protocol, local_addr);
+        Ok(GazeListener {
+            local_addr,
+            queue,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 取出自上次调用以来收到的所有采样
+    pub fn drain(&self) -> Vec<GazeSample> {
+        self.queue
+            .lock()
+            .map(|mut queue| std::mem::take(&mut *queue))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for GazeListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // 等待监听线程退出，确保端口在析构后可以立即重新绑定
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn push_lines(text: &str, queue: &Mutex<Vec<GazeSample>>) {
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        match GazeSample::parse_line(line) {
+            Ok(sample) => {
+                if let Ok(mut queue) = queue.lock() {
+                    queue.push(sample);
+                }
+            }
+            Err(e) => log::warn!("Ignoring gaze line {:?}: {}", line, e),
+        }
+    }
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+fn receive_udp(socket: UdpSocket, queue: Arc<Mutex<Vec<GazeSample>>>, stop: Arc<AtomicBool>) {
+    let mut buf = [0u8; 65536];
+    while !stop.load(Ordering::Relaxed) {
+        match socket.recv(&mut buf) {
+            Ok(len) => push_lines(&String::from_utf8_lossy(&buf[..len]), &queue),
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => {
+                log::error!("Gaze UDP receive failed: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+fn accept_tcp(listener: TcpListener, queue: Arc<Mutex<Vec<GazeSample>>>, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                log::info!("Gaze client connected: {}", peer);
+                let (queue, stop) = (queue.clone(), stop.clone());
+                thread::spawn(move || read_tcp(stream, queue, stop));
+            }
+            Err(e) if is_timeout(&e) => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                log::error!("Gaze TCP accept failed: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+fn read_tcp(stream: TcpStream, queue: Arc<Mutex<Vec<GazeSample>>>, stop: Arc<AtomicBool>) {
+    if stream.set_nonblocking(false).is_err() || stream.set_read_timeout(Some(POLL_INTERVAL)).is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while !stop.load(Ordering::Relaxed) {
+        // 超时返回时 line 里可能已有半行，保留到下次继续读取
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                push_lines(&line, &queue);
+                line.clear();
+            }
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => {
+                log::warn!("Gaze TCP read failed: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::Instant;
+
+    fn wait_for(listener: &GazeListener, count: usize) -> Vec<GazeSample> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut samples = Vec::new();
+        while samples.len() < count && Instant::now() < deadline {
+            samples.extend(listener.drain());
+            thread::sleep(Duration::from_millis(10));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_clock_offset() {
+        // 眼动仪时钟比关卡时间快 1000 秒，大部分采样延迟 20ms 才收到
+        let mut gaze = GazeTracker::default();
+        for i in 0..100 {
+            let t = i as f64 / 120.0;
+            let delay = if i % 10 == 0 { 0.0 } else { 0.02 };
+            gaze.record(t + delay, Some(t + 1000.0), 0.0, 0.0);
+        }
+        gaze.record(1.0, None, 0.0, 0.0);
+        let times: Vec<f64> = gaze.samples().iter().map(|p| p.t).collect();
+        assert!(times[..100].iter().enumerate().all(|(i, t)| (t - i as f64 / 120.0).abs() < 1e-9));
+        assert_eq!(times[100], 1.0);
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let sample = GazeSample::parse_line(r#"{"t": 1.5, "x": 0.25, "y": 0.75}"#).unwrap();
+        assert_eq!(sample, GazeSample { t: Some(1.5), x: 0.25, y: 0.75 });
+        assert_eq!(GazeSample::parse_line(r#"{"x": 1, "y": 2}"#).unwrap().t, None);
+        assert!(GazeSample::parse_line("not json").is_err());
+
+        let space = GazeSpace::Pixels { width: 2560.0, height: 1440.0 };
+        assert_eq!(space.to_logical(1280.0, 720.0, 1920.0, 1080.0), (960.0, 540.0));
+        assert_eq!(GazeSpace::Normalized.to_logical(0.5, 0.5, 1920.0, 1080.0), (960.0, 540.0));
+    }
+
+    #[test]
+    fn test_validate_pixel_space() {
+        assert!(GazeConfig::default().validate().is_ok());
+        let config = |width, height| GazeConfig { space: GazeSpace::Pixels { width, height }, ..GazeConfig::default() };
+        assert!(config(2560.0, 1440.0).validate().is_ok());
+        for (width, height) in [(0.0, 1440.0), (2560.0, -1.0), (f64::NAN, 1440.0), (2560.0, f64::INFINITY)] {
+            let err = config(width, height).validate().unwrap_err();
+            assert!(matches!(err, CoreError::Config(_)), "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_udp_listener() {
+        let listener = GazeListener::bind(GazeProtocol::Udp, 0).unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .send_to(b"{\"t\": 0.0, \"x\": 0.1, \"y\": 0.2}\ngarbage\n{\"x\": 0.3, \"y\": 0.4}\n", listener.local_addr())
+            .unwrap();
+        let samples = wait_for(&listener, 2);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1], GazeSample { t: None, x: 0.3, y: 0.4 });
+
+        // 析构后端口立即可用
+        let port = listener.local_addr().port();
+        drop(listener);
+        assert!(GazeListener::bind(GazeProtocol::Udp, port).is_ok());
+    }
+
+    #[test]
+    fn test_tcp_listener() {
+        let listener = GazeListener::bind(GazeProtocol::Tcp, 0).unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr()).unwrap();
+        stream.write_all(b"{\"t\": 0.0, \"x\": 0.1, \"y\": 0.2}\n{\"t\": 0.01,").unwrap();
+        stream.flush().unwrap();
+        thread::sleep(Duration::from_millis(100));
+        stream.write_all(b" \"x\": 0.3, \"y\": 0.4}\n").unwrap();
+        let samples = wait_for(&listener, 2);
+        assert_eq!(samples, vec![
+            GazeSample { t: Some(0.0), x: 0.1, y: 0.2 },
+            GazeSample { t: Some(0.01), x: 0.3, y: 0.4 },
+        ]);
+    }
+}
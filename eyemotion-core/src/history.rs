@@ -0,0 +1,524 @@
+//! 训练历史：保存每次训练的摘要，并统计每日训练时长与连续训练天数。
+//!
+//! 历史保存在配置目录下的 `history.jsonl`，每行一条 [`SessionRecord`]，只追加写入；
+//! 进行中的训练定期写入 `session.json`（先写临时文件再改名），
+//! 程序崩溃后下次启动时会把它补记为一条未完成的训练。
+
+use crate::config::write_atomic;
+use crate::{CoreError, GameEvent, GameState, GameUpdate, Result, UserConfig};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) const HISTORY_FILE: &str = "history.jsonl";
+pub(crate) const CHECKPOINT_FILE: &str = "session.json";
+/// 训练进行中每累计这么多秒保存一次进度
+const CHECKPOINT_INTERVAL: f64 = 10.0;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// 完成的一个关卡；`score` 为指针跟踪得分，没有指针输入时为空
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageResult {
+    pub stage: i32,
+    pub score: Option<f64>,
+}
+
+/// 一次训练的摘要
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// 唯一编号（开始时间的 Unix 毫秒）
+    pub id: u64,
+    /// 开始与结束时间（Unix 秒）
+    pub started: u64,
+    pub ended: u64,
+    pub program: String,
+    pub stages_completed: Vec<StageResult>,
+    /// 实际训练时长（秒），不含暂停
+    pub duration: f64,
+    pub pauses: u32,
+    /// 是否完成了全部关卡；中途退出或崩溃时为 `false`
+    pub finished: bool,
+}
+
+impl SessionRecord {
+    /// 各关得分的平均值，没有得分时为空
+    pub fn mean_score(&self) -> Option<f64> {
+        let scores: Vec<f64> = self.stages_completed.iter().filter_map(|s| s.score).collect();
+        (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+/// 某一天（本地日期）的训练统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyTotal {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub minutes: f64,
+    pub sessions: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryTotals {
+    pub sessions: u32,
+    pub total_minutes: f64,
+    pub today_minutes: f64,
+    /// 按日期从旧到新排列，只包含有训练的日期
+    pub daily: Vec<DailyTotal>,
+    /// 截至今天（今天还没训练时截至昨天）的连续训练天数
+    pub streak_days: u32,
+    pub longest_streak: u32,
+}
+
+/// 训练历史，同时跟踪当前进行中的训练
+pub struct History {
+    dir: PathBuf,
+    current: Option<SessionRecord>,
+    was_paused: bool,
+    unsaved: f64,
+}
+
+impl History {
+    /// 打开配置目录中的历史
+    pub fn open() -> Result<Self> {
+        Self::open_in(UserConfig::get_config_path()?)
+    }
+
+    /// 打开 `dir` 中的历史，并补记上次崩溃时未结束的训练
+    pub fn open_in(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let history = History { dir, current: None, was_paused: false, unsaved: 0.0 };
+        history.recover()?;
+        Ok(history)
+    }
+
+    fn history_path(&self) -> PathBuf {
+        self.dir.join(HISTORY_FILE)
+    }
+
+    fn checkpoint_path(&self) -> PathBuf {
+        self.dir.join(CHECKPOINT_FILE)
+    }
+
+    fn recover(&self) -> Result<()> {
+        let path = self.checkpoint_path();
+        if !path.exists() {
+            return Ok(());
+        }
+        match serde_json::from_str::<SessionRecord>(&fs::read_to_string(&path)?) {
+            Ok(record) => {
+                log::info!("Recovered unfinished session {}", record.id);
+                self.append(&record)?;
+            }
+            Err(e) => log::warn!("Discarding unreadable session checkpoint: {}", e),
+        }
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    /// 当前进行中的训练
+    pub fn current(&self) -> Option<&SessionRecord> {
+        self.current.as_ref()
+    }
+
+    /// 每次 tick 之后调用：离开开始界面时开始记录，统计时长（按实际推进的模拟时间）、
+    /// 暂停与完成的关卡，收到 `GameOver` 时结束并保存
+    pub fn on_tick(&mut self, state: &GameState, update: &GameUpdate) -> Result<()> {
+        if self.current.is_none() {
+            if state.is_start_screen || state.is_game_over {
+                return Ok(());
+            }
+            self.begin(&state.program.name);
+        }
+        let Some(record) = self.current.as_mut() else {
+            return Ok(());
+        };
+
+        if state.paused && !self.was_paused {
+            record.pauses += 1;
+        }
+        self.was_paused = state.paused;
+        if !state.paused {
+            record.duration += update.stepped;
+            self.unsaved += update.stepped;
+        }
+
+        let mut changed = false;
+        for event in &update.events {
+            match event {
+                GameEvent::StageCompleted { stage, tracking, .. } => {
+                    record.stages_completed.push(StageResult {
+                        stage: *stage,
+                        score: tracking.as_ref().map(|t| t.score),
+                    });
+                    changed = true;
+                }
+                GameEvent::GameOver => record.finished = true,
+                _ => {}
+            }
+        }
+
+        if record.finished {
+            self.finish()?;
+        } else if changed || self.unsaved >= CHECKPOINT_INTERVAL {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self, program: &str) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.current = Some(SessionRecord {
+            id: now.as_millis() as u64,
+            started: now.as_secs(),
+            ended: now.as_secs(),
+            program: program.to_string(),
+            stages_completed: Vec::new(),
+            duration: 0.0,
+            pauses: 0,
+            finished: false,
+        });
+        self.was_paused = false;
+        self.unsaved = 0.0;
+    }
+
+    fn checkpoint(&mut self) -> Result<()> {
+        let path = self.checkpoint_path();
+        if let Some(record) = self.current.as_mut() {
+            record.ended = unix_now();
+            write_atomic(&path, serde_json::to_string(record)?.as_bytes())?;
+        }
+        self.unsaved = 0.0;
+        Ok(())
+    }
+
+    /// 结束当前训练（重新开始、切换程序或退出时调用），写入历史并返回
+    pub fn finish(&mut self) -> Result<Option<SessionRecord>> {
+        let Some(mut record) = self.current.take() else {
+            return Ok(None);
+        };
+        record.ended = unix_now();
+        self.append(&record)?;
+        let checkpoint = self.checkpoint_path();
+        if checkpoint.exists() {
+            fs::remove_file(checkpoint)?;
+        }
+        Ok(Some(record))
+    }
+
+    fn append(&self, record: &SessionRecord) -> Result<()> {
+        let path = self.history_path();
+        // 崩溃时最多留下一行不完整的记录，读取时会跳过；先补上换行，避免与新记录连在一起
+        let mut line = if ends_with_partial_line(&path)? { "\n".to_string() } else { String::new() };
+        line.push_str(&serde_json::to_string(record)?);
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// 已保存的训练，按开始时间从旧到新排列
+    pub fn list(&self) -> Result<Vec<SessionRecord>> {
+        let path = self.history_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut records = Vec::new();
+        for (index, line) in BufReader::new(File::open(&path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<SessionRecord>(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => log::warn!("Skipping unreadable history line {}: {}", index + 1, e),
+            }
+        }
+        records.sort_by_key(|r| r.started);
+        Ok(records)
+    }
+
+    /// 删除一条记录，不存在时返回 `false`
+    pub fn delete(&self, id: u64) -> Result<bool> {
+        let records = self.list()?;
+        let kept: Vec<&SessionRecord> = records.iter().filter(|r| r.id != id).collect();
+        if kept.len() == records.len() {
+            return Ok(false);
+        }
+        let mut content = String::new();
+        for record in kept {
+            content.push_str(&serde_json::to_string(record)?);
+            content.push('\n');
+        }
+        write_atomic(&self.history_path(), content.as_bytes())?;
+        Ok(true)
+    }
+
+    /// 统计已保存的训练；`utc_offset_minutes` 为本地时区相对 UTC 的分钟数，用于划分日期
+    pub fn totals(&self, utc_offset_minutes: i32) -> Result<HistoryTotals> {
+        Ok(totals(&self.list()?, unix_now(), utc_offset_minutes))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn ends_with_partial_line(path: &Path) -> Result<bool> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+/// Unix 秒所在的本地日期（自 1970-01-01 起的天数）
+pub(crate) fn local_day(timestamp: u64, utc_offset_minutes: i32) -> i64 {
+    (timestamp as i64 + utc_offset_minutes as i64 * 60).div_euclid(SECONDS_PER_DAY)
+}
+
+/// 天数转换为 `YYYY-MM-DD`（公历）
+pub(crate) fn format_day(days: i64) -> String {
+    // Howard Hinnant 的 civil_from_days 算法
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 解析 `YYYY-MM-DD` 为自 1970-01-01 起的天数
+pub(crate) fn parse_day(date: &str) -> Result<i64> {
+    let invalid = || CoreError::Config(format!("Invalid date '{}', expected YYYY-MM-DD", date));
+    let mut parts = date.trim().splitn(3, '-');
+    let mut next = || parts.next().and_then(|p| p.parse::<i64>().ok()).ok_or_else(invalid);
+    let (year, month, day) = (next()?, next()?, next()?);
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let month_days = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if !(1..=12).contains(&month) || day < 1 || day > month_days[month as usize - 1] {
+        return Err(invalid());
+    }
+    // Howard Hinnant 的 days_from_civil 算法
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Ok(era * 146_097 + doe - 719_468)
+}
+
+/// Unix 秒格式化为带时区的本地时间，如 `2024-03-01T09:30:00+08:00`
+pub(crate) fn format_local_time(timestamp: u64, utc_offset_minutes: i32) -> String {
+    let local = timestamp as i64 + utc_offset_minutes as i64 * 60;
+    let seconds = local.rem_euclid(SECONDS_PER_DAY);
+    let offset = utc_offset_minutes.abs();
+    format!(
+        "{}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        format_day(local.div_euclid(SECONDS_PER_DAY)),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        if utc_offset_minutes < 0 { '-' } else { '+' },
+        offset / 60,
+        offset % 60
+    )
+}
+
+fn totals(records: &[SessionRecord], now: u64, utc_offset_minutes: i32) -> HistoryTotals {
+    let mut days: Vec<(i64, DailyTotal)> = Vec::new();
+    for record in records {
+        let day = local_day(record.started, utc_offset_minutes);
+        let index = match days.iter().position(|(d, _)| *d == day) {
+            Some(index) => index,
+            None => {
+                let total = DailyTotal { date: format_day(day), minutes: 0.0, sessions: 0 };
+                days.push((day, total));
+                days.len() - 1
+            }
+        };
+        days[index].1.minutes += record.duration / 60.0;
+        days[index].1.sessions += 1;
+    }
+    days.sort_by_key(|(day, _)| *day);
+
+    let today = local_day(now, utc_offset_minutes);
+    let mut longest_streak = 0;
+    let mut run = 0;
+    let mut previous: Option<i64> = None;
+    for (day, _) in &days {
+        run = if previous == Some(day - 1) { run + 1 } else { 1 };
+        longest_streak = longest_streak.max(run);
+        previous = Some(*day);
+    }
+    // 最后一段连续天数在今天或昨天结束时才算当前的连续训练
+    let streak_days = match previous {
+        Some(last) if last >= today - 1 => run,
+        _ => 0,
+    };
+
+    HistoryTotals {
+        sessions: records.len() as u32,
+        total_minutes: days.iter().map(|(_, d)| d.minutes).sum(),
+        today_minutes: days.iter().find(|(d, _)| *d == today).map_or(0.0, |(_, d)| d.minutes),
+        daily: days.into_iter().map(|(_, d)| d).collect(),
+        streak_days,
+        longest_streak,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eyemotion-history-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn record(id: u64, started: u64, duration: f64) -> SessionRecord {
+        SessionRecord {
+            id,
+            started,
+            ended: started + duration as u64,
+            program: "default".to_string(),
+            stages_completed: Vec::new(),
+            duration,
+            pauses: 0,
+            finished: true,
+        }
+    }
+
+    #[test]
+    fn test_session_tracking() {
+        let dir = temp_dir("tracking");
+        let mut history = History::open_in(&dir).unwrap();
+        let mut state = GameState::with_seed(1920.0, 1080.0, Default::default(), 1).unwrap();
+
+        // 开始界面上的 tick 不计入
+        history.on_tick(&state, &GameUpdate::default()).unwrap();
+        assert!(history.current().is_none());
+
+        state.is_start_screen = false;
+        let mut elapsed = 0.0;
+        while !state.is_game_over {
+            if (elapsed as u64 == 20) != state.paused {
+                state.paused = !state.paused;
+            }
+            let update = state.update(0.1).unwrap();
+            history.on_tick(&state, &update).unwrap();
+            elapsed += 0.1;
+        }
+
+        assert!(history.current().is_none());
+        assert!(!dir.join(CHECKPOINT_FILE).exists());
+        let records = history.list().unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert!(record.finished);
+        assert_eq!(record.pauses, 1);
+        assert_eq!(record.stages_completed.len(), state.program.stages.len());
+        assert!(record.mean_score().is_none());
+        assert!((record.duration - (elapsed - 1.0)).abs() < 0.01, "{} {}", record.duration, elapsed);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stall_counts_stepped_time() {
+        let dir = temp_dir("stall");
+        let mut history = History::open_in(&dir).unwrap();
+        let mut state = GameState::with_seed(1920.0, 1080.0, Default::default(), 1).unwrap();
+        state.is_start_screen = false;
+
+        // 卡顿 5 秒，但模拟最多只追 max_catch_up_steps 步
+        let update = state.update(5.0).unwrap();
+        history.on_tick(&state, &update).unwrap();
+        let expected = state.max_catch_up_steps as f64 * state.fixed_dt;
+        assert!((history.current().unwrap().duration - expected).abs() < 1e-9);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_crash_recovery_and_delete() {
+        let dir = temp_dir("recovery");
+        let mut history = History::open_in(&dir).unwrap();
+        let mut state = GameState::with_seed(1920.0, 1080.0, Default::default(), 1).unwrap();
+        state.is_start_screen = false;
+        for _ in 0..150 {
+            let update = state.update(0.1).unwrap();
+            history.on_tick(&state, &update).unwrap();
+        }
+        assert!(dir.join(CHECKPOINT_FILE).exists());
+        // 模拟崩溃：不调用 finish，另有一行写了一半的记录
+        drop(history);
+        let mut file = OpenOptions::new().create(true).append(true).open(dir.join(HISTORY_FILE)).unwrap();
+        file.write_all(b"{\"id\":1,\"sta").unwrap();
+        drop(file);
+
+        let history = History::open_in(&dir).unwrap();
+        let records = history.list().unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].finished);
+        assert!(records[0].duration >= CHECKPOINT_INTERVAL);
+        assert!(!dir.join(CHECKPOINT_FILE).exists());
+
+        assert!(history.delete(records[0].id).unwrap());
+        assert!(!history.delete(records[0].id).unwrap());
+        assert!(history.list().unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_totals_and_streak() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(19_723), "2024-01-01");
+        assert_eq!(format_day(19_782), "2024-02-29");
+        assert_eq!(parse_day("2024-02-29").unwrap(), 19_782);
+        assert!(parse_day("2023-02-29").is_err());
+        assert!(parse_day("2024/01/01").is_err());
+        assert_eq!(format_local_time(19_723 * 86_400 - 1800, 480), "2024-01-01T07:30:00+08:00");
+        assert_eq!(format_local_time(19_723 * 86_400, -330), "2023-12-31T18:30:00-05:30");
+
+        let day = SECONDS_PER_DAY as u64;
+        let today = 20_000 * day + 3600;
+        let records = vec![
+            record(1, today - 10 * day, 600.0),
+            record(2, today - 9 * day, 600.0),
+            record(3, today - 8 * day, 600.0),
+            record(4, today - 2 * day, 300.0),
+            record(5, today - day, 300.0),
+            record(6, today - day + 60, 300.0),
+        ];
+        let result = totals(&records, today, 0);
+        assert_eq!(result.sessions, 6);
+        assert_eq!(result.daily.len(), 5);
+        assert_eq!(result.daily[4].sessions, 2);
+        assert!((result.total_minutes - 45.0).abs() < 1e-9);
+        assert_eq!(result.today_minutes, 0.0);
+        assert_eq!(result.streak_days, 2);
+        assert_eq!(result.longest_streak, 3);
+
+        // 时区把凌晨 1 点的训练划到前一天
+        assert_eq!(totals(&records[..1], today, -120).daily[0].date, format_day(20_000 - 11));
+        assert_eq!(totals(&records[..3], today, 0).streak_days, 0);
+    }
+}
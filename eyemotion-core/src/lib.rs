@@ -1,17 +1,37 @@
+pub mod analysis;
+pub mod audio;
 pub mod audio_config;
 pub mod ball;
 pub mod config;
+pub mod distractor;
 pub mod error;
 pub mod events;
+pub mod export;
 pub mod game_state;
+pub mod gaze;
+pub mod history;
+pub mod profile;
+pub mod program;
+pub mod recording;
 pub mod stage_config;
+pub mod tracking;
 pub mod visual_config;
 
+pub use analysis::{AnalysisParams, Dropout, DropoutKind, PursuitReport, Saccade, StageReport, TimedSample};
+pub use audio::{PcmBuffer, WavFormat, Waveform};
 pub use audio_config::{AudioParams, BGMParams, SFXParams};
-pub use ball::Ball;
+pub use ball::{Ball, Bounce, Wall};
 pub use config::UserConfig;
+pub use distractor::Distractor;
 pub use error::{CoreError, Result};
 pub use events::{GameEvent, GameUpdate};
-pub use game_state::{stage_direction, stage_speed, GameState};
-pub use stage_config::{Axis, MotionType, StageConfig};
+pub use export::{ExportFormat, ExportOptions, ExportedSession, HistoryExport};
+pub use game_state::{GameState, Interpolation, TARGET_BALL};
+pub use gaze::{GazeConfig, GazeListener, GazeProtocol, GazeSample, GazeSpace, GazeTracker};
+pub use history::{DailyTotal, History, HistoryTotals, SessionRecord, StageResult};
+pub use profile::{Profile, ProfileList, Profiles};
+pub use program::TrainingProgram;
+pub use recording::{BallSnapshot, RecordEntry, RecordingHeader, Replay, ReplayStep, SessionRecorder};
+pub use stage_config::{Axis, DepthCurve, DistractorMotion, JumpRule, MotionType, PhasePoint, StageConfig};
+pub use tracking::{PointerSample, Tracker, TrackingReport, Trajectory};
 pub use visual_config::{BackgroundStyle, BallStyle, Color, Theme, UIStyle};
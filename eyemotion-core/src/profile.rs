@@ -0,0 +1,296 @@
+//! 用户档案：多人共用一台电脑时，每个档案有自己的配置（语言、音频、训练程序等）和训练历史。
+//!
+//! 档案列表保存在配置目录下的 `profiles.json`，每个档案的文件在 `profiles/<id>/` 中。
+//! 训练程序与录制仍由所有档案共用。
+
+use crate::config::{write_atomic, CONFIG_FILE};
+use crate::history::{CHECKPOINT_FILE, HISTORY_FILE};
+use crate::{CoreError, History, Result, UserConfig};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PROFILES_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+/// 从单用户版本升级时，原有配置与历史归入此档案
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const DEFAULT_PROFILE_NAME: &str = "Default";
+pub const MAX_PROFILE_NAME_LEN: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    /// 创建时间（Unix 秒）
+    pub created: u64,
+}
+
+/// `profiles.json` 的内容，也是返回给前端的档案列表
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileList {
+    /// 当前使用的档案，下次启动时继续使用
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+pub struct Profiles {
+    dir: PathBuf,
+    list: ProfileList,
+}
+
+impl Profiles {
+    /// 打开配置目录中的档案列表
+    pub fn open() -> Result<Self> {
+        Self::open_in(UserConfig::get_config_path()?)
+    }
+
+    /// 打开 `dir` 中的档案列表；第一次使用时创建默认档案，并把原有的配置与历史移入其中
+    pub fn open_in(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let path = dir.join(PROFILES_FILE);
+        if path.exists() {
+            let mut list: ProfileList = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            if list.profiles.is_empty() {
+                return Err(CoreError::Config(format!("{} contains no profiles", path.display())));
+            }
+            if !list.profiles.iter().any(|p| p.id == list.active) {
+                log::warn!("Active profile '{}' not found, using '{}'", list.active, list.profiles[0].id);
+                list.active = list.profiles[0].id.clone();
+            }
+            return Ok(Profiles { dir, list });
+        }
+
+        let profiles = Profiles {
+            list: ProfileList {
+                active: DEFAULT_PROFILE_ID.to_string(),
+                profiles: vec![Profile {
+                    id: DEFAULT_PROFILE_ID.to_string(),
+                    name: DEFAULT_PROFILE_NAME.to_string(),
+                    created: unix_now(),
+                }],
+            },
+            dir,
+        };
+        let profile_dir = profiles.profile_dir(DEFAULT_PROFILE_ID);
+        fs::create_dir_all(&profile_dir)?;
+        for file in [CONFIG_FILE, HISTORY_FILE, CHECKPOINT_FILE] {
+            let legacy = profiles.dir.join(file);
+            if legacy.exists() {
+                fs::rename(&legacy, profile_dir.join(file))?;
+                log::info!("Moved {} into the default profile", legacy.display());
+            }
+        }
+        profiles.save()?;
+        Ok(profiles)
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        write_atomic(&self.dir.join(PROFILES_FILE), serde_json::to_string_pretty(&self.list)?.as_bytes())
+    }
+
+    pub fn list(&self) -> &ProfileList {
+        &self.list
+    }
+
+    pub fn active(&self) -> &Profile {
+        self.get(&self.list.active).unwrap_or(&self.list.profiles[0])
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Profile> {
+        self.list.profiles.iter().find(|p| p.id == id)
+    }
+
+    fn require(&self, id: &str) -> Result<&Profile> {
+        self.get(id).ok_or_else(|| CoreError::Config(format!("Profile not found: {}", id)))
+    }
+
+    pub fn profile_dir(&self, id: &str) -> PathBuf {
+        self.dir.join(PROFILES_DIR).join(id)
+    }
+
+    /// 当前档案的配置
+    pub fn load_config(&self) -> UserConfig {
+        UserConfig::load_from(&self.profile_dir(&self.active().id).join(CONFIG_FILE))
+    }
+
+    /// 当前档案的训练历史
+    pub fn open_history(&self) -> Result<History> {
+        History::open_in(self.profile_dir(&self.active().id))
+    }
+
+    /// 去掉首尾空白，检查名称非空、不过长且与其他档案不重名（不区分大小写）
+    fn validate_name(&self, name: &str, except: Option<&str>) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(CoreError::Config("Profile name must not be empty".to_string()));
+        }
+        if name.chars().count() > MAX_PROFILE_NAME_LEN {
+            return Err(CoreError::Config(format!(
+                "Profile name must be at most {} characters",
+                MAX_PROFILE_NAME_LEN
+            )));
+        }
+        let taken = self
+            .list
+            .profiles
+            .iter()
+            .any(|p| Some(p.id.as_str()) != except && p.name.to_lowercase() == name.to_lowercase());
+        if taken {
+            return Err(CoreError::Config(format!("Profile '{}' already exists", name)));
+        }
+        Ok(name.to_string())
+    }
+
+    /// 新建档案（使用默认配置），不切换当前档案
+    pub fn create(&mut self, name: &str) -> Result<Profile> {
+        let name = self.validate_name(name, None)?;
+        let mut n = self.list.profiles.len() + 1;
+        while self.get(&format!("p{}", n)).is_some() || self.profile_dir(&format!("p{}", n)).exists() {
+            n += 1;
+        }
+        let profile = Profile { id: format!("p{}", n), name, created: unix_now() };
+
+        let config = UserConfig::load_from(&self.profile_dir(&profile.id).join(CONFIG_FILE));
+        config.save()?;
+        self.list.profiles.push(profile.clone());
+        self.save()?;
+        Ok(profile)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<()> {
+        self.require(id)?;
+        let name = self.validate_name(name, Some(id))?;
+        if let Some(profile) = self.list.profiles.iter_mut().find(|p| p.id == id) {
+            profile.name = name;
+        }
+        self.save()
+    }
+
+    /// 切换当前档案，并记住以便下次启动时使用
+    pub fn switch(&mut self, id: &str) -> Result<()> {
+        self.require(id)?;
+        self.list.active = id.to_string();
+        self.save()
+    }
+
+    /// 删除档案及其配置和历史；不能删除最后一个档案，删除当前档案时切换到剩下的第一个
+    pub fn delete(&mut self, id: &str) -> Result<()> {
+        self.require(id)?;
+        if self.list.profiles.len() == 1 {
+            return Err(CoreError::Config("Cannot delete the last profile".to_string()));
+        }
+        self.list.profiles.retain(|p| p.id != id);
+        if self.list.active == id {
+            self.list.active = self.list.profiles[0].id.clone();
+        }
+        self.save()?;
+
+        let dir = self.profile_dir(id);
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameState;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eyemotion-profiles-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_legacy_files_move_to_default_profile() {
+        let dir = temp_dir("legacy");
+        let mut legacy = UserConfig::default();
+        legacy.language = "zh".to_string();
+        legacy.save_to(&dir.join(CONFIG_FILE)).unwrap();
+        fs::write(dir.join(HISTORY_FILE), "").unwrap();
+
+        let profiles = Profiles::open_in(&dir).unwrap();
+        assert_eq!(profiles.active().id, DEFAULT_PROFILE_ID);
+        assert_eq!(profiles.load_config().language, "zh");
+        assert!(!dir.join(CONFIG_FILE).exists());
+        assert!(dir.join("profiles/default").join(HISTORY_FILE).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_profiles_have_separate_config() {
+        let dir = temp_dir("separate");
+        let mut profiles = Profiles::open_in(&dir).unwrap();
+        let mut config = profiles.load_config();
+        config.language = "zh".to_string();
+        config.save().unwrap();
+
+        let child = profiles.create("  Child ").unwrap();
+        assert_eq!(child.name, "Child");
+        assert!(profiles.create("child").is_err());
+        assert!(profiles.create(" ").is_err());
+
+        profiles.switch(&child.id).unwrap();
+        assert_eq!(profiles.load_config().language, "en");
+
+        // 重新打开后仍是上次使用的档案
+        let mut profiles = Profiles::open_in(&dir).unwrap();
+        assert_eq!(profiles.active().id, child.id);
+        profiles.rename(&child.id, "Kid").unwrap();
+        assert_eq!(profiles.active().name, "Kid");
+        profiles.switch(DEFAULT_PROFILE_ID).unwrap();
+        assert_eq!(profiles.load_config().language, "zh");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_switch_restores_profile_stage() {
+        let dir = temp_dir("stage");
+        let mut profiles = Profiles::open_in(&dir).unwrap();
+        let mut config = profiles.load_config();
+        config.last_played_stage = 3;
+        config.save().unwrap();
+        let other = profiles.create("Other").unwrap();
+
+        let mut state = GameState::new(1920.0, 1080.0);
+        state.reset_to_stage(profiles.load_config().last_played_stage);
+        assert_eq!(state.stage, 3);
+
+        profiles.switch(&other.id).unwrap();
+        state.reset_to_stage(profiles.load_config().last_played_stage);
+        assert_eq!(state.stage, 1);
+
+        profiles.switch(DEFAULT_PROFILE_ID).unwrap();
+        state.reset_to_stage(profiles.load_config().last_played_stage);
+        assert_eq!(state.stage, 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_delete_profile() {
+        let dir = temp_dir("delete");
+        let mut profiles = Profiles::open_in(&dir).unwrap();
+        assert!(profiles.delete(DEFAULT_PROFILE_ID).is_err());
+
+        let other = profiles.create("Clinic").unwrap();
+        profiles.switch(&other.id).unwrap();
+        profiles.delete(&other.id).unwrap();
+        assert_eq!(profiles.active().id, DEFAULT_PROFILE_ID);
+        assert!(!profiles.profile_dir(&other.id).exists());
+        assert!(profiles.delete("missing").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
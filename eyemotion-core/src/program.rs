@@ -0,0 +1,267 @@
+use crate::{CoreError, Result, StageConfig, UserConfig};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_PROGRAM_NAME: &str = "default";
+
+/// 一套完整的训练程序：有序的关卡列表与关卡间倒计时
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainingProgram {
+    #[serde(default)]
+    pub name: String,
+    /// 关卡开始前的倒计时（秒）
+    #[serde(default = "default_transition_duration")]
+    pub transition_duration: f64,
+    pub stages: Vec<StageConfig>,
+}
+
+fn default_transition_duration() -> f64 {
+    3.0
+}
+
+impl Default for TrainingProgram {
+    fn default() -> Self {
+        TrainingProgram {
+            name: DEFAULT_PROGRAM_NAME.to_string(),
+            transition_duration: default_transition_duration(),
+            stages: StageConfig::default_program(),
+        }
+    }
+}
+
+impl TrainingProgram {
+    pub fn from_json_str(content: &str) -> Result<Self> {
+        let program: TrainingProgram =
+            serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(content))
+                .map_err(|e| parse_error(e.path(), e.inner()))?;
+        program.validate()?;
+        Ok(program)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let program: TrainingProgram = serde_path_to_error::deserialize(toml::Deserializer::new(content))
+            .map_err(|e| parse_error(e.path(), e.inner()))?;
+        program.validate()?;
+        Ok(program)
+    }
+
+    /// 根据扩展名 (`.json` / `.toml`) 读取程序文件；未填写名称时使用文件名
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut program = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json_str(&content),
+            Some("toml") => Self::from_toml_str(&content),
+            _ => Err(CoreError::Config(format!(
+                "unsupported program file: {}",
+                path.display()
+            ))),
+        }?;
+
+        if program.name.is_empty() {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                program.name = stem.to_string();
+            }
+        }
+        Ok(program)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !self.transition_duration.is_finite() || self.transition_duration < 0.0 {
+            return Err(CoreError::Config(
+                "field `transition_duration` must be a non-negative number".to_string(),
+            ));
+        }
+        if self.stages.is_empty() {
+            return Err(CoreError::Config("training program has no stages".to_string()));
+        }
+        for (index, stage) in self.stages.iter().enumerate() {
+            stage
+                .validate()
+                .map_err(|field| {
+                    CoreError::Config(format!("stage {}: field `{}` is invalid", index + 1, field))
+                })?;
+        }
+        Ok(())
+    }
+
+    /// 用户自定义程序所在目录（配置目录下的 `programs`）
+    pub fn programs_dir() -> Result<PathBuf> {
+        Ok(UserConfig::get_config_path()?.join("programs"))
+    }
+
+    /// 内置默认程序加上 `programs` 目录中的所有程序，按文件名排序
+    pub fn list_available() -> Result<Vec<TrainingProgram>> {
+        let mut programs = vec![TrainingProgram::default()];
+        let dir = Self::programs_dir()?;
+        if !dir.is_dir() {
+            return Ok(programs);
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("json") | Some("toml")
+                )
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match Self::load(&path) {
+                Ok(program) => programs.push(program),
+                Err(e) => log::warn!("Skipping training program {}: {}", path.display(), e),
+            }
+        }
+        Ok(programs)
+    }
+
+    pub fn find(name: &str) -> Result<TrainingProgram> {
+        Self::list_available()?
+            .into_iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| CoreError::Config(format!("unknown training program: {}", name)))
+    }
+}
+
+/// 解析错误与 `validate` 的格式一致：`stages[2].duration` 报告为 `stage 3: field `duration``
+fn parse_error(path: &serde_path_to_error::Path, error: &dyn std::fmt::Display) -> CoreError {
+    let mut segments = path.iter().peekable();
+    let mut prefix = String::new();
+    if let Some(serde_path_to_error::Segment::Map { key }) = segments.peek() {
+        if key == "stages" {
+            segments.next();
+            if let Some(serde_path_to_error::Segment::Seq { index }) = segments.peek() {
+                prefix = format!("stage {}: ", index + 1);
+                segments.next();
+            }
+        }
+    }
+    let field = segments.map(|s| s.to_string()).collect::<Vec<_>>().join(".");
+    let field = field.trim_start_matches('.').replace(".[", "[");
+    if field.is_empty() || field == "?" {
+        CoreError::Config(format!("{}{}", prefix, error))
+    } else {
+        CoreError::Config(format!("{}field `{}`: {}", prefix, field, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MotionType, PhasePoint};
+
+    #[test]
+    fn test_default_program_is_valid() {
+        let program = TrainingProgram::default();
+        assert!(program.validate().is_ok());
+        assert_eq!(program.stages.len(), 5);
+    }
+
+    #[test]
+    fn test_load_toml() {
+        let program = TrainingProgram::from_toml_str(
+            r#"
+            name = "short"
+            transition_duration = 2.0
+
+            [[stages]]
+            speed = 800.0
+            angle_range = [10.0, 20.0]
+            duration = 30.0
+            motion_type = { Linear = { primary_axis = "Horizontal" } }
+
+            [[stages]]
+            speed = 0.0
+            angle_range = [0.0, 0.0]
+            duration = 20.0
+            motion_type = { Circular = { angular_speed = 1.0, reverse_at = { Seconds = 5.0 } } }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(program.name, "short");
+        assert_eq!(program.transition_duration, 2.0);
+        assert_eq!(program.stages.len(), 2);
+        assert_eq!(
+            program.stages[1].motion_type,
+            MotionType::Circular {
+                angular_speed: 1.0,
+                reverse_at: PhasePoint::Seconds(5.0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_stage_reports_index_and_field() {
+        let err = TrainingProgram::from_json_str(
+            r#"{
+                "stages": [
+                    { "speed": 1000.0, "angle_range": [10.0, 20.0], "duration": 45.0,
+                      "motion_type": { "Linear": { "primary_axis": "Vertical" } } },
+                    { "speed": 1000.0, "angle_range": [10.0, 20.0], "duration": -1.0,
+                      "motion_type": { "Linear": { "primary_axis": "Vertical" } } }
+                ]
+            }"#,
+        )
+        .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("stage 2"), "{}", message);
+        assert!(message.contains("duration"), "{}", message);
+    }
+
+    #[test]
+    fn test_phase_point_must_fall_within_stage() {
+        let circular = |reverse_at: PhasePoint| TrainingProgram {
+            stages: vec![StageConfig {
+                duration: 20.0,
+                motion_type: MotionType::Circular { angular_speed: 1.0, reverse_at },
+                ..StageConfig::default()
+            }],
+            ..TrainingProgram::default()
+        };
+        assert!(circular(PhasePoint::Seconds(0.0)).validate().is_ok());
+        assert!(circular(PhasePoint::Seconds(20.0)).validate().is_ok());
+        for seconds in [-1.0, 20.5, f64::NAN, f64::INFINITY] {
+            let message = circular(PhasePoint::Seconds(seconds)).validate().unwrap_err().to_string();
+            assert!(message.contains("stage 1: field `motion_type.reverse_at`"), "{}", message);
+        }
+
+        let mut program = circular(PhasePoint::default());
+        program.stages[0].motion_type = MotionType::CenterLines {
+            vertical_speed_ratio: 1.0,
+            switch_at: PhasePoint::Seconds(25.0),
+        };
+        let message = program.validate().unwrap_err().to_string();
+        assert!(message.contains("field `motion_type.switch_at`"), "{}", message);
+    }
+
+    #[test]
+    fn test_malformed_stage_field_reports_index_and_field() {
+        let stage = r#"{ "speed": 1000.0, "angle_range": [10.0, 20.0], "duration": 45.0,
+                         "motion_type": { "Linear": { "primary_axis": "Vertical" } } }"#;
+        let json = format!(
+            r#"{{ "stages": [{0}, {0}, {1}] }}"#,
+            stage,
+            stage.replace("45.0", "\"abc\"")
+        );
+        let err = TrainingProgram::from_json_str(&json).unwrap_err();
+        assert!(matches!(err, CoreError::Config(_)), "{:?}", err);
+        let message = err.to_string();
+        assert!(message.contains("stage 3: field `duration`"), "{}", message);
+
+        let err = TrainingProgram::from_toml_str(
+            r#"
+            [[stages]]
+            speed = 1000.0
+            angle_range = [10.0, 20.0]
+            duration = "abc"
+            motion_type = { Linear = { primary_axis = "Vertical" } }
+            "#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, CoreError::Config(_)), "{:?}", err);
+        assert!(err.to_string().contains("stage 1: field `duration`"), "{}", err);
+    }
+}
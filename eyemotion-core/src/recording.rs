@@ -0,0 +1,381 @@
+//! 训练过程录制与回放。
+//!
+//! 录制文件为 JSON-lines：第一行是 [`RecordingHeader`]（包含开始时完整的 `GameState`，
+//! 其中有随机数生成器的状态），之后每行一个 [`RecordEntry`]。由于所有随机选择
+//! （初始方向、随机位置、反弹扰动等）都来自状态中的种子化 RNG，
+//! 按顺序重放输入即可完全复现整个训练过程。
+
+use crate::{CoreError, GameEvent, GameState, GazeSample, GazeSpace, PointerSample, Result, UserConfig};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 当前录制格式版本
+pub const RECORDING_VERSION: u32 = 1;
+/// 录制目录中最多保留的文件数，超出时删除最旧的
+pub const MAX_RECORDINGS: usize = 20;
+/// 每写入这么多条记录刷新一次缓冲区
+const FLUSH_INTERVAL: usize = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    /// 开始录制的时间（Unix 秒）
+    pub created: u64,
+    pub state: GameState,
+}
+
+/// 某一时刻目标小球的状态，用于回放时检查是否与录制一致
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BallSnapshot {
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+}
+
+impl BallSnapshot {
+    pub fn of(state: &GameState) -> Self {
+        BallSnapshot {
+            x: state.ball.x,
+            y: state.ball.y,
+            vx: state.ball.vx,
+            vy: state.ball.vy,
+        }
+    }
+}
+
+/// 一条录制记录：对 `GameState` 的一次操作，`Tick` 同时保存操作后的结果。
+/// 每个文件只录制一次训练，开始、重置与切换程序都发生在文件头之前，不需要记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RecordEntry {
+    Tick {
+        dt: f64,
+        stage: i32,
+        ball: BallSnapshot,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        events: Vec<GameEvent>,
+    },
+    Pause { paused: bool },
+    Resize { w: f64, h: f64 },
+    NextStage,
+    PrevStage,
+    SetFixedDt { fixed_dt: f64 },
+    Pointer { sample: PointerSample },
+    Gaze { sample: GazeSample, space: GazeSpace },
+}
+
+impl RecordEntry {
+    /// 把记录的操作应用到 `state`，返回产生的事件
+    pub fn apply(&self, state: &mut GameState) -> Result<Vec<GameEvent>> {
+        let mut events = Vec::new();
+        match self {
+            RecordEntry::Tick { dt, .. } => events = state.update(*dt)?.events,
+            RecordEntry::Pause { paused } => state.paused = *paused,
+            RecordEntry::Resize { w, h } => state.resize(*w, *h),
+            RecordEntry::NextStage => state.next_stage(&mut events),
+            RecordEntry::PrevStage => state.prev_stage(&mut events),
+            RecordEntry::SetFixedDt { fixed_dt } => state.set_fixed_dt(*fixed_dt)?,
+            RecordEntry::Pointer { sample } => state.record_pointer(*sample),
+            RecordEntry::Gaze { sample, space } => state.record_gaze(*sample, *space),
+        }
+        Ok(events)
+    }
+}
+
+/// 把训练过程写入录制文件
+pub struct SessionRecorder<W: Write = BufWriter<File>> {
+    writer: W,
+    path: Option<PathBuf>,
+    pending: usize,
+}
+
+impl SessionRecorder {
+    /// 录制文件所在目录（配置目录下的 `recordings`）
+    pub fn recordings_dir() -> Result<PathBuf> {
+        Ok(UserConfig::get_config_path()?.join("recordings"))
+    }
+
+    /// 在录制目录中新建文件开始录制，并清理过旧的录制
+    pub fn start(state: &GameState) -> Result<Self> {
+        let dir = Self::recordings_dir()?;
+        fs::create_dir_all(&dir)?;
+        prune_recordings(&dir, MAX_RECORDINGS - 1)?;
+
+        let created = unix_now();
+        let mut path = dir.join(format!("session-{}.jsonl", created));
+        let mut suffix = 1;
+        while path.exists() {
+            path = dir.join(format!("session-{}-{}.jsonl", created, suffix));
+            suffix += 1;
+        }
+
+        let mut recorder = SessionRecorder::new(BufWriter::new(File::create(&path)?), state)?;
+        recorder.path = Some(path);
+        Ok(recorder)
+    }
+
+    /// 列出录制目录中的文件，按时间从旧到新排序
+    pub fn list() -> Result<Vec<PathBuf>> {
+        list_recordings(&Self::recordings_dir()?)
+    }
+}
+
+impl<W: Write> SessionRecorder<W> {
+    /// 写入文件头，之后的记录都基于此时的 `state`
+    pub fn new(mut writer: W, state: &GameState) -> Result<Self> {
+        let header = RecordingHeader {
+            version: RECORDING_VERSION,
+            created: unix_now(),
+            state: state.clone(),
+        };
+        serde_json::to_writer(&mut writer, &header)?;
+        writer.write_all(b"\n")?;
+        Ok(SessionRecorder { writer, path: None, pending: 0 })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn record(&mut self, entry: &RecordEntry) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")?;
+        self.pending += 1;
+        if self.pending >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 记录一次 tick 的输入与结果
+    pub fn record_tick(&mut self, dt: f64, state: &GameState, events: &[GameEvent]) -> Result<()> {
+        self.record(&RecordEntry::Tick {
+            dt,
+            stage: state.stage,
+            ball: BallSnapshot::of(state),
+            events: events.to_vec(),
+        })
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.pending = 0;
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(mut self) -> Result<W> {
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// 回放一步的结果
+#[derive(Debug, Clone)]
+pub struct ReplayStep {
+    pub entry: RecordEntry,
+    pub events: Vec<GameEvent>,
+    /// `Tick` 记录的结果与录制时不一致
+    pub diverged: bool,
+}
+
+/// 逐条回放录制文件
+pub struct Replay {
+    pub header: RecordingHeader,
+    entries: Vec<RecordEntry>,
+    cursor: usize,
+    state: GameState,
+}
+
+impl Replay {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut lines = reader.lines();
+        let header_line = lines
+            .next()
+            .ok_or_else(|| CoreError::Config("empty recording".to_string()))??;
+        let header: RecordingHeader = serde_json::from_str(&header_line)?;
+        if header.version > RECORDING_VERSION {
+            return Err(CoreError::Config(format!(
+                "unsupported recording version {} (expected at most {})",
+                header.version, RECORDING_VERSION
+            )));
+        }
+
+        let mut entries = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                // 程序崩溃时最后一行可能不完整，忽略即可
+                Err(e) => {
+                    log::warn!("Recording truncated at entry {}: {}", index + 1, e);
+                    break;
+                }
+            }
+        }
+
+        Ok(Replay {
+            state: header.state.clone(),
+            header,
+            entries,
+            cursor: 0,
+        })
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn entries(&self) -> &[RecordEntry] {
+        &self.entries
+    }
+
+    /// 已回放的记录数
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.entries.len()
+    }
+
+    /// 回放下一条记录，全部回放完后返回 `None`
+    pub fn step(&mut self) -> Option<Result<ReplayStep>> {
+        let entry = self.entries.get(self.cursor)?.clone();
+        self.cursor += 1;
+
+        Some(entry.apply(&mut self.state).map(|events| {
+            let diverged = match &entry {
+                RecordEntry::Tick { stage, ball, events: recorded, .. } => {
+                    *stage != self.state.stage || *ball != BallSnapshot::of(&self.state) || *recorded != events
+                }
+                _ => false,
+            };
+            ReplayStep { entry, events, diverged }
+        }))
+    }
+
+    /// 回放到结尾，返回第一条结果不一致的记录序号
+    pub fn run_to_end(&mut self) -> Result<Option<usize>> {
+        let mut first_divergence = None;
+        while let Some(step) = self.step() {
+            if step?.diverged && first_divergence.is_none() {
+                first_divergence = Some(self.cursor - 1);
+            }
+        }
+        Ok(first_divergence)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn list_recordings(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or(UNIX_EPOCH);
+            (modified, entry.path())
+        })
+        .collect();
+    paths.sort();
+    Ok(paths.into_iter().map(|(_, path)| path).collect())
+}
+
+/// 删除最旧的录制，只保留 `keep` 个
+fn prune_recordings(dir: &Path, keep: usize) -> Result<()> {
+    let paths = list_recordings(dir)?;
+    let excess = paths.len().saturating_sub(keep);
+    for path in &paths[..excess] {
+        if let Err(e) = fs::remove_file(path) {
+            log::warn!("Failed to remove old recording {}: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrainingProgram;
+
+    fn apply(state: &mut GameState, recorder: &mut SessionRecorder<Vec<u8>>, entry: RecordEntry) {
+        entry.apply(state).unwrap();
+        recorder.record(&entry).unwrap();
+    }
+
+    /// 模拟一段训练：开始后录制调整窗口、带指针输入的 tick、暂停、跳关
+    fn record_session() -> (Vec<u8>, GameState) {
+        let mut state = GameState::with_seed(800.0, 600.0, TrainingProgram::default(), 11).unwrap();
+        state.is_start_screen = false;
+        let mut recorder = SessionRecorder::new(Vec::new(), &state).unwrap();
+
+        apply(&mut state, &mut recorder, RecordEntry::Resize { w: 1920.0, h: 1080.0 });
+        for i in 0..600 {
+            let sample = PointerSample { x: i as f64, y: 300.0 };
+            apply(&mut state, &mut recorder, RecordEntry::Pointer { sample });
+            let dt = if i % 7 == 0 { 0.033 } else { 1.0 / 60.0 };
+            let events = state.update(dt).unwrap().events;
+            recorder.record_tick(dt, &state, &events).unwrap();
+            match i {
+                200 => apply(&mut state, &mut recorder, RecordEntry::Pause { paused: true }),
+                250 => apply(&mut state, &mut recorder, RecordEntry::Pause { paused: false }),
+                400 => apply(&mut state, &mut recorder, RecordEntry::NextStage),
+                _ => {}
+            }
+        }
+        (recorder.into_inner().unwrap(), state)
+    }
+
+    #[test]
+    fn test_replay_matches_recording() {
+        let (bytes, state) = record_session();
+        let mut replay = Replay::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(replay.header.version, RECORDING_VERSION);
+        assert_eq!(replay.entries().len(), 1 + 600 * 2 + 3);
+
+        assert_eq!(replay.run_to_end().unwrap(), None);
+        assert!(replay.is_finished());
+        assert_eq!(replay.state().stage, state.stage);
+        assert_eq!(BallSnapshot::of(replay.state()), BallSnapshot::of(&state));
+    }
+
+    #[test]
+    fn test_replay_detects_divergence() {
+        let (bytes, _) = record_session();
+        let mut replay = Replay::from_reader(bytes.as_slice()).unwrap();
+        // 改变初始状态后，第一条 tick 的结果就会不同
+        replay.state.ball.x += 5.0;
+        assert_eq!(replay.run_to_end().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_truncated_and_future_recordings() {
+        let (mut bytes, _) = record_session();
+        bytes.extend_from_slice(b"{\"type\": \"Ti");
+        let replay = Replay::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(replay.entries().len(), 1 + 600 * 2 + 3);
+
+        let state = GameState::with_seed(800.0, 600.0, TrainingProgram::default(), 1).unwrap();
+        let header = RecordingHeader { version: RECORDING_VERSION + 1, created: 0, state };
+        let json = serde_json::to_string(&header).unwrap();
+        assert!(Replay::from_reader(json.as_bytes()).is_err());
+    }
+}
@@ -1,17 +1,214 @@
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+use rand::Rng;
+use std::f64::consts::PI;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct StageConfig {
     pub speed: f64,
+    /// 初始方向的角度范围（度），具体含义取决于 `primary_axis`
     pub angle_range: (f64, f64),
     pub motion_type: MotionType,
+    /// 关卡时长（秒）
+    pub duration: f64,
+    /// 景深通道：小球视觉大小随时间变化，用于远近聚焦练习
+    #[serde(default)]
+    pub depth: Option<DepthCurve>,
+    /// 干扰球：与目标同时出现、样式不同，各自按自己的运动方式移动
+    #[serde(default)]
+    pub distractors: Vec<DistractorMotion>,
+    /// 是否启用小球之间的碰撞
+    #[serde(default)]
+    pub ball_collisions: bool,
+}
+
+/// 干扰球的运动方式
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum DistractorMotion {
+    /// 碰到边界弹开；方向与水平方向夹角在 `angle_range`（度）内，象限随机
+    Linear { speed: f64, angle_range: (f64, f64) },
+    /// 绕屏幕中心做圆周运动，`radius_ratio` 为占最大轨道半径的比例
+    Circular { angular_speed: f64, radius_ratio: f64 },
+    /// 同 `MotionType::Lissajous`
+    Lissajous {
+        freq_x: f64,
+        freq_y: f64,
+        phase: f64,
+        amplitude: f64,
+    },
+}
+
+impl DistractorMotion {
+    fn validate(&self) -> std::result::Result<(), &'static str> {
+        match *self {
+            DistractorMotion::Linear { speed, angle_range: (lo, hi) } => {
+                if !speed.is_finite() || speed < 0.0 {
+                    return Err("speed");
+                }
+                if !lo.is_finite() || !hi.is_finite() || lo > hi {
+                    return Err("angle_range");
+                }
+            }
+            DistractorMotion::Circular { angular_speed, radius_ratio } => {
+                if !angular_speed.is_finite() {
+                    return Err("angular_speed");
+                }
+                if !(0.0..=1.0).contains(&radius_ratio) {
+                    return Err("radius_ratio");
+                }
+            }
+            DistractorMotion::Lissajous { freq_x, freq_y, phase, amplitude } => {
+                if !freq_x.is_finite() || freq_x < 0.0 {
+                    return Err("freq_x");
+                }
+                if !freq_y.is_finite() || freq_y < 0.0 {
+                    return Err("freq_y");
+                }
+                if !phase.is_finite() {
+                    return Err("phase");
+                }
+                if !(amplitude > 0.0 && amplitude <= 1.0) {
+                    return Err("amplitude");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 景深缩放比例的上限，再大时小球直径接近屏幕高度的一半
+pub const MAX_DEPTH_SCALE: f64 = 5.0;
+
+/// 小球缩放比例随关卡时间变化的曲线
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum DepthCurve {
+    /// 在 `min_scale` 与 `max_scale` 之间按正弦往复，周期 `period` 秒，从中间值开始变大
+    Sine {
+        min_scale: f64,
+        max_scale: f64,
+        period: f64,
+    },
+    /// 在整个关卡内从 `from` 线性变化到 `to`
+    Ramp { from: f64, to: f64 },
+}
+
+impl DepthCurve {
+    /// 关卡时间 `t` 时的缩放比例
+    pub fn scale_at(&self, t: f64, duration: f64) -> f64 {
+        match *self {
+            DepthCurve::Sine { min_scale, max_scale, period } => {
+                let mid = (min_scale + max_scale) / 2.0;
+                let half_range = (max_scale - min_scale) / 2.0;
+                mid + half_range * (2.0 * PI * t / period).sin()
+            }
+            DepthCurve::Ramp { from, to } => {
+                let progress = (t / duration).clamp(0.0, 1.0);
+                from + (to - from) * progress
+            }
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let positive = |v: f64| v.is_finite() && v > 0.0;
+        let scale = |v: f64| positive(v) && v <= MAX_DEPTH_SCALE;
+        match *self {
+            DepthCurve::Sine { min_scale, max_scale, period } => {
+                scale(min_scale) && scale(max_scale) && min_scale <= max_scale && positive(period)
+            }
+            DepthCurve::Ramp { from, to } => scale(from) && scale(to),
+        }
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum MotionType {
-    Linear { primary_axis: Axis },
-    Circular { angular_speed: f64 },
+    /// 碰到边界弹开；`bounce_jitter` 为每次反弹时随机偏转的最大弧度
+    Linear {
+        primary_axis: Axis,
+        #[serde(default)]
+        bounce_jitter: f64,
+    },
+    /// 先沿水平中线运动，到 `switch_at` 后改为沿垂直中线运动
+    CenterLines {
+        vertical_speed_ratio: f64,
+        #[serde(default)]
+        switch_at: PhasePoint,
+    },
+    /// 绕屏幕中心做圆周运动，到 `reverse_at` 时短暂停顿并反向
+    Circular {
+        angular_speed: f64,
+        #[serde(default)]
+        reverse_at: PhasePoint,
+    },
+    /// 螺旋运动：以 `angular_speed` (rad/s) 绕中心旋转，轨道半径以 `radial_speed` (px/s)
+    /// 从中心扩展到边缘再收缩回中心，往复进行
+    Spiral {
+        angular_speed: f64,
+        radial_speed: f64,
+    },
+    /// 参数曲线 x = sin(2π·freq_x·t + phase), y = sin(2π·freq_y·t)，
+    /// `amplitude` 为占可用半宽/半高的比例 (0..=1]；freq_x : freq_y = 1 : 2 即为横向 8 字
+    Lissajous {
+        freq_x: f64,
+        freq_y: f64,
+        phase: f64,
+        amplitude: f64,
+    },
+    /// 扫视训练：目标停留 `dwell` 秒后按 `rule` 跳到新位置，
+    /// `amplitude` 为跳跃范围占可用半宽/半高的比例 (0..=1]
+    Saccade {
+        dwell: f64,
+        rule: JumpRule,
+        amplitude: f64,
+    },
+}
+
+/// 扫视目标的跳跃规则
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum JumpRule {
+    /// 范围内随机位置
+    Random,
+    /// 水平中线上左右交替
+    Alternating,
+    /// 按行依次遍历固定网格，格数不超过 [`MAX_GRID_CELLS`]
+    Grid { columns: u32, rows: u32 },
+    /// 反扫视：提示随机出现在左侧或右侧，正确反应是看向水平镜像位置
+    AntiSaccade,
+}
+
+/// 扫视网格的格数上限
+pub const MAX_GRID_CELLS: u32 = 1024;
+
+/// 关卡内部的阶段切换点：按关卡时长的比例，或按关卡开始后的秒数
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PhasePoint {
+    Fraction(f64),
+    Seconds(f64),
+}
+
+impl Default for PhasePoint {
+    fn default() -> Self {
+        PhasePoint::Fraction(0.5)
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+impl PhasePoint {
+    /// 换算为关卡开始后的秒数
+    pub fn resolve(&self, duration: f64) -> f64 {
+        match *self {
+            PhasePoint::Fraction(f) => duration * f,
+            PhasePoint::Seconds(s) => s,
+        }
+    }
+
+    /// 切换点必须落在时长为 `duration` 的关卡之内
+    fn is_valid(&self, duration: f64) -> bool {
+        match *self {
+            PhasePoint::Fraction(f) => (0.0..=1.0).contains(&f),
+            PhasePoint::Seconds(s) => (0.0..=duration).contains(&s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Axis {
     Horizontal,
     Vertical,
@@ -25,7 +222,188 @@ impl Default for StageConfig {
             angle_range: (10.0, 20.0),
             motion_type: MotionType::Linear {
                 primary_axis: Axis::Horizontal,
+                bounce_jitter: 0.0,
+            },
+            duration: 45.0,
+            depth: None,
+            distractors: Vec::new(),
+            ball_collisions: false,
+        }
+    }
+}
+
+impl StageConfig {
+    /// 默认训练程序：原有的五个关卡
+    pub fn default_program() -> Vec<StageConfig> {
+        vec![
+            // Stage 1: 标准碰撞反弹
+            StageConfig::default(),
+            // Stage 2: 仅保留垂直运动
+            StageConfig {
+                speed: 1100.0, // 从 1250.0 降低到 1100.0
+                angle_range: (7.0, 17.0),
+                motion_type: MotionType::Linear {
+                    primary_axis: Axis::Vertical,
+                    bounce_jitter: 0.0,
+                },
+                ..StageConfig::default()
+            },
+            // Stage 3: 前半程水平居中运动，后半程垂直居中运动 (x0.7)
+            StageConfig {
+                speed: 1625.0,
+                motion_type: MotionType::CenterLines {
+                    vertical_speed_ratio: 0.7,
+                    switch_at: PhasePoint::Fraction(0.5),
+                },
+                ..StageConfig::default()
             },
+            // Stage 4: 碰到边界弹开，并在反弹时加入约 +/- 6 度的随机偏移
+            StageConfig {
+                speed: 1500.0, // 从 1800.0 降低到 1500.0
+                angle_range: (20.0, 70.0),
+                motion_type: MotionType::Linear {
+                    primary_axis: Axis::Diagonal,
+                    bounce_jitter: 0.1,
+                },
+                ..StageConfig::default()
+            },
+            // Stage 5: 圆周运动
+            StageConfig {
+                speed: 0.0,
+                motion_type: MotionType::Circular {
+                    angular_speed: 1.2,
+                    reverse_at: PhasePoint::Fraction(0.5),
+                },
+                ..StageConfig::default()
+            },
+        ]
+    }
+
+    /// 关卡开始时的随机初始方向（未归一化）
+    pub fn initial_direction<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        let (lo, hi) = self.angle_range;
+        let angle_deg = if lo < hi { rng.gen_range(lo..hi) } else { lo };
+        let angle_rad = angle_deg * PI / 180.0;
+
+        match self.motion_type {
+            MotionType::CenterLines { .. } => {
+                // 初始强制水平向左或向右
+                if rng.gen_bool(0.5) {
+                    (1.0, 0.0)
+                } else {
+                    (-1.0, 0.0)
+                }
+            }
+            MotionType::Linear { primary_axis: Axis::Vertical, .. } => {
+                // 垂直方向带有一点随机偏角
+                let vx = if rng.gen_bool(0.5) { angle_rad.sin() } else { -angle_rad.sin() };
+                let vy = if rng.gen_bool(0.5) { angle_rad.cos() } else { -angle_rad.cos() };
+                (vx, vy)
+            }
+            MotionType::Linear { primary_axis: Axis::Diagonal, .. } => {
+                // 更像对角线的随机角度，不再仅限于垂直附近
+                if rng.gen_bool(0.5) {
+                    (angle_rad.sin(), angle_rad.cos())
+                } else {
+                    (angle_rad.sin(), -angle_rad.cos())
+                }
+            }
+            MotionType::Linear { primary_axis: Axis::Horizontal, .. }
+            | MotionType::Circular { .. }
+            | MotionType::Spiral { .. }
+            | MotionType::Lissajous { .. }
+            | MotionType::Saccade { .. } => {
+                if rng.gen_bool(0.5) {
+                    (angle_rad.cos(), angle_rad.sin())
+                } else {
+                    (-angle_rad.cos(), angle_rad.sin())
+                }
+            }
+        }
+    }
+
+    /// 校验关卡参数，失败时返回出错的字段名
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if !self.speed.is_finite() || self.speed < 0.0 {
+            return Err("speed".to_string());
+        }
+        let (lo, hi) = self.angle_range;
+        if !lo.is_finite() || !hi.is_finite() || lo > hi {
+            return Err("angle_range".to_string());
+        }
+        if !self.duration.is_finite() || self.duration <= 0.0 {
+            return Err("duration".to_string());
+        }
+        if let Some(depth) = self.depth {
+            if !depth.is_valid() {
+                return Err("depth".to_string());
+            }
+        }
+        for (index, distractor) in self.distractors.iter().enumerate() {
+            if let Err(field) = distractor.validate() {
+                return Err(format!("distractors[{}].{}", index, field));
+            }
+        }
+        match self.motion_type {
+            MotionType::Linear { bounce_jitter, .. } => {
+                if !bounce_jitter.is_finite() || bounce_jitter < 0.0 {
+                    return Err("motion_type.bounce_jitter".to_string());
+                }
+            }
+            MotionType::CenterLines { vertical_speed_ratio, switch_at } => {
+                if !vertical_speed_ratio.is_finite() || vertical_speed_ratio <= 0.0 {
+                    return Err("motion_type.vertical_speed_ratio".to_string());
+                }
+                if !switch_at.is_valid(self.duration) {
+                    return Err("motion_type.switch_at".to_string());
+                }
+            }
+            MotionType::Circular { angular_speed, reverse_at } => {
+                if !angular_speed.is_finite() {
+                    return Err("motion_type.angular_speed".to_string());
+                }
+                if !reverse_at.is_valid(self.duration) {
+                    return Err("motion_type.reverse_at".to_string());
+                }
+            }
+            MotionType::Spiral { angular_speed, radial_speed } => {
+                if !angular_speed.is_finite() {
+                    return Err("motion_type.angular_speed".to_string());
+                }
+                if !radial_speed.is_finite() || radial_speed <= 0.0 {
+                    return Err("motion_type.radial_speed".to_string());
+                }
+            }
+            MotionType::Lissajous { freq_x, freq_y, phase, amplitude } => {
+                if !freq_x.is_finite() || freq_x < 0.0 {
+                    return Err("motion_type.freq_x".to_string());
+                }
+                if !freq_y.is_finite() || freq_y < 0.0 {
+                    return Err("motion_type.freq_y".to_string());
+                }
+                if !phase.is_finite() {
+                    return Err("motion_type.phase".to_string());
+                }
+                if !(amplitude > 0.0 && amplitude <= 1.0) {
+                    return Err("motion_type.amplitude".to_string());
+                }
+            }
+            MotionType::Saccade { dwell, rule, amplitude } => {
+                if !dwell.is_finite() || dwell <= 0.0 {
+                    return Err("motion_type.dwell".to_string());
+                }
+                if let JumpRule::Grid { columns, rows } = rule {
+                    match columns.checked_mul(rows) {
+                        Some(cells) if cells > 0 && cells <= MAX_GRID_CELLS => {}
+                        _ => return Err("motion_type.rule".to_string()),
+                    }
+                }
+                // 跳跃范围已扣除边缘留白，amplitude 不超过 1 时目标不会进入留白
+                if !(amplitude > 0.0 && amplitude <= 1.0) {
+                    return Err("motion_type.amplitude".to_string());
+                }
+            }
         }
+        Ok(())
     }
 }
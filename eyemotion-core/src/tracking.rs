@@ -0,0 +1,219 @@
+use crate::analysis::{interpolate, TimedSample};
+use serde::{Deserialize, Serialize};
+
+/// 指针与目标的距离不超过 `半径 × ON_TARGET_RADIUS_RATIO` 时视为跟上目标
+pub const ON_TARGET_RADIUS_RATIO: f64 = 2.0;
+/// 相邻两个指针采样的最大间隔，超过时视为中断，不计入跟踪时间
+const MAX_SAMPLE_GAP: f64 = 0.25;
+/// 延迟估计的搜索范围与步长（秒）
+const MAX_LAG: f64 = 0.5;
+const LAG_STEP: f64 = 0.01;
+/// 实时报告只用最近这段时间的采样估计延迟
+const LIVE_LAG_WINDOW: f64 = 2.0;
+/// 估计延迟所需的最少采样数
+const MIN_LAG_SAMPLES: usize = 10;
+
+/// 前端发送的一个指针（鼠标 / 触摸）采样，使用逻辑坐标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointerSample {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// 一个关卡内的跟踪精度统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackingReport {
+    pub samples: u32,
+    /// 最近一次采样与目标的距离
+    pub last_distance: f64,
+    pub mean_distance: f64,
+    /// 指针处于目标范围内的累计时间（秒）
+    pub time_on_target: f64,
+    /// 有效采样覆盖的累计时间（秒）
+    pub tracked_time: f64,
+    /// 指针落后目标的估计时间（秒），采样不足时为 `None`
+    pub lag: Option<f64>,
+    /// 0..=100：跟上目标的时间占比
+    pub score: f64,
+}
+
+/// 当前关卡内目标的运动轨迹（按关卡时间排序），供指针与眼动评分对齐
+#[derive(Debug, Clone, Default)]
+pub struct Trajectory {
+    points: Vec<TimedSample>,
+}
+
+impl Trajectory {
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// 记录关卡时间 `t` 时的目标位置
+    pub fn record(&mut self, t: f64, x: f64, y: f64) {
+        self.points.push(TimedSample { t, x, y });
+    }
+
+    /// 在轨迹上按时间线性插值；早于轨迹起点时为 `None`
+    pub fn at(&self, t: f64) -> Option<(f64, f64)> {
+        interpolate(&self.points, t)
+    }
+
+    pub fn samples(&self) -> &[TimedSample] {
+        &self.points
+    }
+}
+
+/// 记录指针采样，对照目标轨迹计算跟踪精度
+#[derive(Debug, Clone, Default)]
+pub struct Tracker {
+    pointer: Vec<TimedSample>,
+    last_on_target: bool,
+    distance_sum: f64,
+    report: TrackingReport,
+}
+
+impl Tracker {
+    /// 开始新的关卡，清空所有记录
+    pub fn reset(&mut self) {
+        *self = Tracker::default();
+    }
+
+    /// 记录关卡时间 `t` 时的指针位置，与当前目标位置比较
+    pub fn record_pointer(&mut self, t: f64, sample: PointerSample, target: (f64, f64), target_radius: f64) {
+        let distance = (sample.x - target.0).hypot(sample.y - target.1);
+        let on_target = distance <= target_radius * ON_TARGET_RADIUS_RATIO;
+
+        if let Some(prev) = self.pointer.last() {
+            let gap = t - prev.t;
+            if gap > 0.0 && gap <= MAX_SAMPLE_GAP {
+                self.report.tracked_time += gap;
+                if self.last_on_target {
+                    self.report.time_on_target += gap;
+                }
+            }
+        }
+
+        self.pointer.push(TimedSample { t, x: sample.x, y: sample.y });
+        self.last_on_target = on_target;
+        self.distance_sum += distance;
+
+        let report = &mut self.report;
+        report.samples += 1;
+        report.last_distance = distance;
+        report.mean_distance = self.distance_sum / report.samples as f64;
+        report.score = if report.tracked_time > 0.0 {
+            100.0 * report.time_on_target / report.tracked_time
+        } else {
+            0.0
+        };
+    }
+
+    pub fn samples(&self) -> &[TimedSample] {
+        &self.pointer
+    }
+
+    /// 实时报告：延迟只根据最近的采样估计
+    pub fn live_report(&self, trajectory: &Trajectory) -> Option<TrackingReport> {
+        let last = self.pointer.last()?;
+        let mut report = self.report.clone();
+        report.lag = self.estimate_lag(trajectory, last.t - LIVE_LAG_WINDOW);
+        Some(report)
+    }
+
+    /// 关卡结束时的汇总报告
+    pub fn summary(&self, trajectory: &Trajectory) -> Option<TrackingReport> {
+        if self.pointer.is_empty() {
+            return None;
+        }
+        let mut report = self.report.clone();
+        report.lag = self.estimate_lag(trajectory, f64::NEG_INFINITY);
+        Some(report)
+    }
+
+    /// 找出使指针与"延迟后的目标"平均距离最小的延迟
+    fn estimate_lag(&self, trajectory: &Trajectory, since: f64) -> Option<f64> {
+        let samples: Vec<&TimedSample> = self.pointer.iter().filter(|p| p.t >= since).collect();
+        if samples.len() < MIN_LAG_SAMPLES {
+            return None;
+        }
+
+        let mut best: Option<(f64, f64)> = None;
+        let steps = (MAX_LAG / LAG_STEP).round() as usize;
+        for i in 0..=steps {
+            let lag = i as f64 * LAG_STEP;
+            let mut sum = 0.0;
+            let mut count = 0;
+            for p in &samples {
+                if let Some((tx, ty)) = trajectory.at(p.t - lag) {
+                    sum += (p.x - tx).hypot(p.y - ty);
+                    count += 1;
+                }
+            }
+            if count >= MIN_LAG_SAMPLES {
+                let mean = sum / count as f64;
+                if best.is_none_or(|(_, best_mean)| mean < best_mean) {
+                    best = Some((lag, mean));
+                }
+            }
+        }
+        best.map(|(lag, _)| lag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 目标以 500 px/s 水平运动，指针落后 `lag` 秒跟随，60 Hz 采样
+    fn follow(lag: f64) -> (Tracker, Trajectory) {
+        let mut tracker = Tracker::default();
+        let mut trajectory = Trajectory::default();
+        let target_x = |t: f64| 100.0 + 500.0 * t;
+        for i in 0..=2400 {
+            let t = i as f64 / 240.0;
+            trajectory.record(t, target_x(t), 500.0);
+        }
+        for i in 0..=600 {
+            let t = i as f64 / 60.0;
+            let sample = PointerSample { x: target_x((t - lag).max(0.0)), y: 500.0 };
+            tracker.record_pointer(t, sample, (target_x(t), 500.0), 40.0);
+        }
+        (tracker, trajectory)
+    }
+
+    #[test]
+    fn test_perfect_tracking() {
+        let (tracker, trajectory) = follow(0.0);
+        let report = tracker.summary(&trajectory).unwrap();
+        assert_eq!(report.samples, 601);
+        assert_eq!(report.mean_distance, 0.0);
+        assert!((report.score - 100.0).abs() < 1e-9);
+        assert!((report.tracked_time - 10.0).abs() < 1e-9);
+        assert_eq!(report.lag, Some(0.0));
+    }
+
+    #[test]
+    fn test_lagging_pointer() {
+        // 落后 0.2s 即 100px，超出 2 倍半径 (80px)
+        let (tracker, trajectory) = follow(0.2);
+        let report = tracker.summary(&trajectory).unwrap();
+        assert!(report.score < 5.0);
+        assert!(report.mean_distance > 90.0);
+        assert!((report.lag.unwrap() - 0.2).abs() < 1e-9);
+        assert!((tracker.live_report(&trajectory).unwrap().lag.unwrap() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gaps_are_not_counted() {
+        let mut tracker = Tracker::default();
+        let sample = PointerSample { x: 0.0, y: 0.0 };
+        tracker.record_pointer(0.0, sample, (0.0, 0.0), 10.0);
+        tracker.record_pointer(0.1, sample, (0.0, 0.0), 10.0);
+        tracker.record_pointer(5.0, sample, (0.0, 0.0), 10.0);
+        let trajectory = Trajectory::default();
+        let report = tracker.summary(&trajectory).unwrap();
+        assert!((report.tracked_time - 0.1).abs() < 1e-9);
+        assert!(tracker.live_report(&trajectory).unwrap().lag.is_none());
+        assert!(Tracker::default().summary(&trajectory).is_none());
+    }
+}
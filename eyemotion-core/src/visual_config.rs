@@ -2,6 +2,8 @@
 pub struct Theme {
     pub background: BackgroundStyle,
     pub ball: BallStyle,
+    /// 干扰球样式，与目标小球区分开
+    pub distractor: BallStyle,
     pub ui: UIStyle,
 }
 
@@ -63,6 +65,24 @@ impl Default for Theme {
                 },
                 radius_ratio: 1.0 / 40.0,
             },
+            distractor: BallStyle {
+                gradient_start: Color {
+                    r: 148,
+                    g: 163,
+                    b: 184,
+                },
+                gradient_end: Color {
+                    r: 100,
+                    g: 116,
+                    b: 139,
+                },
+                outline_color: Color {
+                    r: 203,
+                    g: 213,
+                    b: 225,
+                },
+                radius_ratio: 1.0 / 40.0,
+            },
             ui: UIStyle {
                 title_color: Color {
                     r: 235,
@@ -0,0 +1,233 @@
+//! 无界面运行 `GameState`，输出小球轨迹与事件，用于调参、覆盖率检查和 CI 回归测试。
+
+use eyemotion_core::{GameEvent, GameState, Result, TrainingProgram};
+use serde::Serialize;
+use std::io::{self, Write};
+
+pub mod render;
+
+/// 覆盖率统计使用的网格大小
+pub const COVERAGE_COLUMNS: usize = 16;
+pub const COVERAGE_ROWS: usize = 9;
+/// 未指定时长时，在程序总时长之外额外允许的模拟时间（秒）
+const EXTRA_TIME: f64 = 60.0;
+
+#[derive(Debug, Clone)]
+pub struct SimOptions {
+    pub program: TrainingProgram,
+    pub seed: u64,
+    pub width: f64,
+    pub height: f64,
+    /// 每秒调用 `update` 的次数（模拟的帧率）
+    pub tick_rate: f64,
+    /// 物理步长，`None` 时使用 `GameState` 的默认值
+    pub fixed_dt: Option<f64>,
+    /// 模拟时长（秒），`None` 时运行到训练结束
+    pub duration: Option<f64>,
+}
+
+/// 每一帧结束时的小球状态
+#[derive(Debug, Clone, Serialize)]
+pub struct TrajectoryRow {
+    pub time: f64,
+    pub stage: i32,
+    pub stage_time: f64,
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+    pub radius: f64,
+    /// 处于关卡之间的过渡阶段
+    pub transitioning: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRow {
+    pub time: f64,
+    pub stage: i32,
+    pub event: GameEvent,
+}
+
+/// 小球中心经过的屏幕网格比例（只统计关卡进行中的帧）
+#[derive(Debug, Clone, Serialize)]
+pub struct Coverage {
+    pub columns: usize,
+    pub rows: usize,
+    pub visited_cells: usize,
+    pub fraction: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub program: String,
+    pub seed: u64,
+    pub ticks: usize,
+    pub duration: f64,
+    pub last_stage: i32,
+    pub game_over: bool,
+    pub bounces: usize,
+    pub coverage: Coverage,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimOutput {
+    pub trajectory: Vec<TrajectoryRow>,
+    pub events: Vec<EventRow>,
+    pub summary: Summary,
+}
+
+pub fn run(options: &SimOptions) -> Result<SimOutput> {
+    let mut state = GameState::with_seed(options.width, options.height, options.program.clone(), options.seed)?;
+    if let Some(fixed_dt) = options.fixed_dt {
+        state.set_fixed_dt(fixed_dt)?;
+    }
+    state.is_start_screen = false;
+
+    let dt = 1.0 / options.tick_rate;
+    let limit = options.duration.unwrap_or_else(|| {
+        let program = &options.program;
+        let stages: f64 = program.stages.iter().map(|s| s.duration).sum();
+        stages + program.transition_duration * program.stages.len() as f64 + EXTRA_TIME
+    });
+
+    let mut trajectory = Vec::new();
+    let mut events = Vec::new();
+    let mut visited = [false; COVERAGE_COLUMNS * COVERAGE_ROWS];
+    let mut time = 0.0;
+    while time < limit && !state.is_game_over {
+        let update = state.update(dt)?;
+        time += dt;
+
+        events.extend(update.events.into_iter().map(|event| EventRow {
+            time,
+            stage: state.stage,
+            event,
+        }));
+        trajectory.push(TrajectoryRow {
+            time,
+            stage: state.stage,
+            stage_time: state.stage_elapsed,
+            x: state.ball.x,
+            y: state.ball.y,
+            vx: state.ball.vx,
+            vy: state.ball.vy,
+            radius: state.ball.radius,
+            transitioning: state.is_transitioning,
+        });
+
+        if !state.is_transitioning {
+            let column = (state.ball.x / options.width * COVERAGE_COLUMNS as f64).floor();
+            let row = (state.ball.y / options.height * COVERAGE_ROWS as f64).floor();
+            let column = (column.max(0.0) as usize).min(COVERAGE_COLUMNS - 1);
+            let row = (row.max(0.0) as usize).min(COVERAGE_ROWS - 1);
+            visited[row * COVERAGE_COLUMNS + column] = true;
+        }
+    }
+
+    let visited_cells = visited.iter().filter(|v| **v).count();
+    let summary = Summary {
+        program: options.program.name.clone(),
+        seed: options.seed,
+        ticks: trajectory.len(),
+        duration: time,
+        last_stage: state.stage,
+        game_over: state.is_game_over,
+        bounces: events
+            .iter()
+            .filter(|e| matches!(e.event, GameEvent::BallBounced { .. }))
+            .count(),
+        coverage: Coverage {
+            columns: COVERAGE_COLUMNS,
+            rows: COVERAGE_ROWS,
+            visited_cells,
+            fraction: visited_cells as f64 / visited.len() as f64,
+        },
+    };
+
+    Ok(SimOutput { trajectory, events, summary })
+}
+
+pub fn write_trajectory_csv<W: Write>(rows: &[TrajectoryRow], mut out: W) -> io::Result<()> {
+    writeln!(out, "time,stage,stage_time,x,y,vx,vy,radius,transitioning")?;
+    for r in rows {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{}",
+            r.time, r.stage, r.stage_time, r.x, r.y, r.vx, r.vy, r.radius, r.transitioning
+        )?;
+    }
+    Ok(())
+}
+
+/// 事件的 CSV：`type` 列为事件类型，`data` 列为完整事件的 JSON
+pub fn write_events_csv<W: Write>(rows: &[EventRow], mut out: W) -> io::Result<()> {
+    writeln!(out, "time,stage,type,data")?;
+    for r in rows {
+        let data = serde_json::to_value(&r.event)?;
+        let kind = data["type"].as_str().unwrap_or_default().to_string();
+        writeln!(out, "{},{},{},{}", r.time, r.stage, kind, csv_quote(&data.to_string()))?;
+    }
+    Ok(())
+}
+
+fn csv_quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(seed: u64) -> SimOptions {
+        SimOptions {
+            program: TrainingProgram::default(),
+            seed,
+            width: 1920.0,
+            height: 1080.0,
+            tick_rate: 60.0,
+            fixed_dt: None,
+            duration: Some(20.0),
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_output() {
+        let a = run(&options(3)).unwrap();
+        let b = run(&options(3)).unwrap();
+        assert_eq!(a.trajectory.len(), 1200);
+        let last = |o: &SimOutput| {
+            let r = o.trajectory.last().unwrap();
+            (r.x, r.y)
+        };
+        assert_eq!(last(&a), last(&b));
+        assert_eq!(a.summary.bounces, b.summary.bounces);
+        assert!(a.summary.bounces > 0);
+        assert!(a.summary.coverage.visited_cells > 0);
+    }
+
+    #[test]
+    fn test_runs_to_game_over() {
+        let output = run(&SimOptions { duration: None, ..options(1) }).unwrap();
+        assert!(output.summary.game_over);
+        assert_eq!(output.summary.last_stage, 5);
+        assert!(output
+            .events
+            .iter()
+            .any(|e| matches!(e.event, GameEvent::GameOver)));
+    }
+
+    #[test]
+    fn test_csv_output() {
+        let output = run(&SimOptions { duration: Some(5.0), ..options(2) }).unwrap();
+        let mut trajectory = Vec::new();
+        write_trajectory_csv(&output.trajectory, &mut trajectory).unwrap();
+        let trajectory = String::from_utf8(trajectory).unwrap();
+        assert_eq!(trajectory.lines().count(), output.trajectory.len() + 1);
+
+        let mut events = Vec::new();
+        write_events_csv(&output.events, &mut events).unwrap();
+        let events = String::from_utf8(events).unwrap();
+        let first = events.lines().nth(1).unwrap();
+        assert!(first.contains(",BallBounced,\"{\"\""), "{}", first);
+    }
+}
@@ -0,0 +1,244 @@
+use clap::{Parser, ValueEnum};
+use eyemotion_core::audio::{self, DEFAULT_SAMPLE_RATE};
+use eyemotion_core::{AudioParams, Theme, TrainingProgram, WavFormat};
+use eyemotion_sim::{render, run, write_events_csv, write_trajectory_csv, SimOptions};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SampleFormat {
+    Pcm16,
+    Float32,
+}
+
+/// 无界面运行 EyeMotion 训练，输出小球轨迹与事件
+#[derive(Debug, Parser)]
+#[command(name = "eyemotion-sim", version)]
+struct Args {
+    /// 训练程序：内置名称、programs 目录中的名称，或 .json/.toml 文件路径
+    #[arg(short, long, default_value = "default")]
+    program: String,
+
+    /// 随机种子，相同种子输出完全相同
+    #[arg(short, long, default_value_t = 1)]
+    seed: u64,
+
+    /// 逻辑屏幕宽度
+    #[arg(long, default_value_t = 1920.0)]
+    width: f64,
+
+    /// 逻辑屏幕高度
+    #[arg(long, default_value_t = 1080.0)]
+    height: f64,
+
+    /// 每秒 tick 次数（模拟的帧率）
+    #[arg(long, default_value_t = 60.0)]
+    tick_rate: f64,
+
+    /// 物理步长（秒），默认 1/240
+    #[arg(long)]
+    fixed_dt: Option<f64>,
+
+    /// 模拟时长（秒），默认运行到训练结束
+    #[arg(short, long)]
+    duration: Option<f64>,
+
+    #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// 轨迹输出文件，默认写到标准输出
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// 事件输出文件，不指定时不输出事件
+    #[arg(short, long)]
+    events: Option<PathBuf>,
+
+    /// 把小球路径渲染为 SVG 文件
+    #[arg(long)]
+    svg: Option<PathBuf>,
+
+    /// 把覆盖热力图渲染为 PNG 文件
+    #[arg(long)]
+    heatmap: Option<PathBuf>,
+
+    /// 只渲染指定关卡，默认渲染所有关卡
+    #[arg(long)]
+    stage: Option<i32>,
+
+    /// 把背景音乐渲染为 WAV 文件；指定 --bgm-wav 或 --sfx-wav 时只导出音频，不运行训练
+    #[arg(long)]
+    bgm_wav: Option<PathBuf>,
+
+    /// 把碰撞音效渲染为 WAV 文件
+    #[arg(long)]
+    sfx_wav: Option<PathBuf>,
+
+    /// 音频参数 JSON 文件（与配置中的 audio 字段相同），默认使用内置参数
+    #[arg(long)]
+    audio: Option<PathBuf>,
+
+    /// 背景音乐时长（秒），默认为完整循环一次
+    #[arg(long)]
+    bgm_duration: Option<f64>,
+
+    #[arg(long, default_value_t = DEFAULT_SAMPLE_RATE)]
+    sample_rate: u32,
+
+    #[arg(long, value_enum, default_value_t = SampleFormat::Pcm16)]
+    sample_format: SampleFormat,
+}
+
+fn load_program(name: &str) -> eyemotion_core::Result<TrainingProgram> {
+    let path = Path::new(name);
+    if path.is_file() {
+        TrainingProgram::load(path)
+    } else {
+        TrainingProgram::find(name)
+    }
+}
+
+fn open_output(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    })
+}
+
+fn load_audio(path: Option<&Path>) -> eyemotion_core::Result<AudioParams> {
+    let params: AudioParams = match path {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => AudioParams::default(),
+    };
+    params.validate()?;
+    Ok(params)
+}
+
+fn export_audio(args: &Args) -> eyemotion_core::Result<()> {
+    let params = load_audio(args.audio.as_deref())?;
+    let format = match args.sample_format {
+        SampleFormat::Pcm16 => WavFormat::Pcm16,
+        SampleFormat::Float32 => WavFormat::Float32,
+    };
+    if let Some(path) = &args.bgm_wav {
+        let bgm = match args.bgm_duration {
+            Some(duration) => audio::render_bgm_for(&params.bgm, duration, args.sample_rate),
+            None => audio::render_bgm(&params.bgm, audio::bgm_loop_beats(&params.bgm), args.sample_rate),
+        };
+        bgm.save_wav(path, format)?;
+        eprintln!("{}: {:.2} s", path.display(), bgm.duration());
+    }
+    if let Some(path) = &args.sfx_wav {
+        let sfx = audio::render_bounce(&params.sfx, args.sample_rate);
+        sfx.save_wav(path, format)?;
+        eprintln!("{}: {:.2} s", path.display(), sfx.duration());
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.bgm_wav.is_some() || args.sfx_wav.is_some() {
+        if !(audio::MIN_SAMPLE_RATE..=audio::MAX_SAMPLE_RATE).contains(&args.sample_rate) {
+            eprintln!(
+                "error: --sample-rate must be between {} and {}",
+                audio::MIN_SAMPLE_RATE,
+                audio::MAX_SAMPLE_RATE
+            );
+            return ExitCode::FAILURE;
+        }
+        if args.bgm_duration.is_some_and(|d| !(d.is_finite() && d > 0.0)) {
+            eprintln!("error: --bgm-duration must be positive");
+            return ExitCode::FAILURE;
+        }
+        return match export_audio(&args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+    if !(args.tick_rate.is_finite() && args.tick_rate > 0.0) {
+        eprintln!("error: --tick-rate must be positive");
+        return ExitCode::FAILURE;
+    }
+    if !(args.width.is_finite() && args.width > 0.0 && args.height.is_finite() && args.height > 0.0) {
+        eprintln!("error: --width and --height must be positive");
+        return ExitCode::FAILURE;
+    }
+
+    let program = match load_program(&args.program) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let options = SimOptions {
+        program,
+        seed: args.seed,
+        width: args.width,
+        height: args.height,
+        tick_rate: args.tick_rate,
+        fixed_dt: args.fixed_dt,
+        duration: args.duration,
+    };
+    let output = match run(&options) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let written = (|| -> io::Result<()> {
+        let mut out = open_output(args.output.as_deref())?;
+        match args.format {
+            Format::Csv => write_trajectory_csv(&output.trajectory, &mut out)?,
+            Format::Json => serde_json::to_writer_pretty(&mut out, &output.trajectory)?,
+        }
+        out.flush()?;
+
+        if let Some(path) = &args.events {
+            let mut out = open_output(Some(path))?;
+            match args.format {
+                Format::Csv => write_events_csv(&output.events, &mut out)?,
+                Format::Json => serde_json::to_writer_pretty(&mut out, &output.events)?,
+            }
+            out.flush()?;
+        }
+
+        let rows = render::active_rows(&output.trajectory, args.stage);
+        let theme = Theme::default();
+        if let Some(path) = &args.svg {
+            std::fs::write(path, render::render_svg(&rows, &theme, args.width, args.height))?;
+        }
+        if let Some(path) = &args.heatmap {
+            let mut out = open_output(Some(path))?;
+            render::render_heatmap_png(&rows, &theme, args.width, args.height, &mut out)?;
+            out.flush()?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = written {
+        eprintln!("error: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    // 摘要写到标准错误，方便与标准输出中的轨迹分开
+    match serde_json::to_string_pretty(&output.summary) {
+        Ok(summary) => eprintln!("{}", summary),
+        Err(e) => eprintln!("error: {}", e),
+    }
+    ExitCode::SUCCESS
+}
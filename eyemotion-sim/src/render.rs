@@ -0,0 +1,260 @@
+//! 把模拟得到的轨迹渲染成 SVG 路径图和 PNG 覆盖热力图，不需要 GPU 或显示器。
+//!
+//! 背景与前端渲染器一致：以 `grid_size` 为边长的深浅棋盘格。
+
+use crate::TrajectoryRow;
+use eyemotion_core::{Color, Theme};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+/// 相邻两个采样相距超过屏幕高度的这一比例时断开路径（扫视跳跃、换关重置）
+const JUMP_RATIO: f64 = 0.25;
+
+/// 需要渲染的采样：关卡进行中，且属于 `stage`（为 `None` 时不限关卡）
+pub fn active_rows(rows: &[TrajectoryRow], stage: Option<i32>) -> Vec<&TrajectoryRow> {
+    rows.iter()
+        .filter(|r| !r.transitioning && stage.is_none_or(|s| r.stage == s))
+        .collect()
+}
+
+/// 棋盘格的几何，与前端 `drawBackground` 一致：格子从原点开始排列，
+/// 颜色的奇偶由居中偏移决定
+struct Grid {
+    size: f64,
+    offset_x: f64,
+    offset_y: f64,
+    columns: usize,
+    rows: usize,
+}
+
+impl Grid {
+    fn new(theme: &Theme, width: f64, height: f64) -> Self {
+        let size = theme.background.grid_size.max(1.0);
+        Grid {
+            size,
+            offset_x: (width % size) / 2.0,
+            offset_y: (height % size) / 2.0,
+            columns: (width / size).ceil().max(1.0) as usize,
+            rows: (height / size).ceil().max(1.0) as usize,
+        }
+    }
+
+    fn cell_of(&self, x: f64, y: f64) -> (usize, usize) {
+        let i = (x / self.size).floor().max(0.0) as usize;
+        let j = (y / self.size).floor().max(0.0) as usize;
+        (i.min(self.columns - 1), j.min(self.rows - 1))
+    }
+
+    fn color<'a>(&self, theme: &'a Theme, i: usize, j: usize) -> &'a Color {
+        // 与 JS 的 Math.round 相同：.5 向正无穷取整
+        let js_round = |v: f64| (v + 0.5).floor() as i64;
+        let grid_x = js_round(i as f64 - self.offset_x / self.size);
+        let grid_y = js_round(j as f64 - self.offset_y / self.size);
+        if (grid_x + grid_y).rem_euclid(2) == 0 {
+            &theme.background.grid_color_dark
+        } else {
+            &theme.background.grid_color_light
+        }
+    }
+}
+
+fn hex(color: &Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn lerp(a: &Color, b: &Color, t: f64) -> [u8; 3] {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    [mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b)]
+}
+
+/// 渲染小球中心的运动路径
+pub fn render_svg(rows: &[&TrajectoryRow], theme: &Theme, width: f64, height: f64) -> String {
+    let grid = Grid::new(theme, width, height);
+    let ball = &theme.ball;
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<defs><linearGradient id="ball" x1="0" y1="0" x2="1" y2="1"><stop offset="0" stop-color="{}"/><stop offset="1" stop-color="{}"/></linearGradient></defs>"#,
+        hex(&ball.gradient_start),
+        hex(&ball.gradient_end)
+    );
+
+    for j in 0..grid.rows {
+        for i in 0..grid.columns {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="{}"/>"#,
+                i as f64 * grid.size,
+                j as f64 * grid.size,
+                hex(grid.color(theme, i, j)),
+                s = grid.size
+            );
+        }
+    }
+
+    if let (Some(first), Some(last)) = (rows.first(), rows.last()) {
+        let mut d = String::new();
+        let mut prev: Option<&TrajectoryRow> = None;
+        for row in rows {
+            let jumped = prev.is_none_or(|p| (row.x - p.x).hypot(row.y - p.y) > JUMP_RATIO * height);
+            let _ = write!(d, "{}{:.1},{:.1} ", if jumped { "M" } else { "L" }, row.x, row.y);
+            prev = Some(row);
+        }
+        let stroke = (first.radius / 8.0).max(1.0);
+        let _ = writeln!(
+            svg,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{:.1}" stroke-linejoin="round" stroke-opacity="0.8"/>"#,
+            d.trim_end(),
+            hex(&ball.gradient_start),
+            stroke
+        );
+        // 起点与终点画出实际大小的小球
+        for (row, opacity) in [(first, 0.5), (last, 1.0)] {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="url(#ball)" fill-opacity="{}" stroke="{}" stroke-width="2"/>"#,
+                row.x,
+                row.y,
+                row.radius,
+                opacity,
+                hex(&ball.outline_color)
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// 热力图每边的最大像素数
+pub const MAX_HEATMAP_SIZE: f64 = 16_384.0;
+
+/// 每个网格的停留时间热力图：未经过的格子保持背景色，
+/// 经过的格子按停留比例从 `gradient_start` 过渡到 `gradient_end`
+pub fn render_heatmap_png<W: Write>(
+    rows: &[&TrajectoryRow],
+    theme: &Theme,
+    width: f64,
+    height: f64,
+    out: W,
+) -> io::Result<()> {
+    for (name, size) in [("width", width), ("height", height)] {
+        if !(size.is_finite() && size.round() >= 1.0 && size.round() <= MAX_HEATMAP_SIZE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("heatmap {} must be between 1 and {}", name, MAX_HEATMAP_SIZE),
+            ));
+        }
+    }
+    let grid = Grid::new(theme, width, height);
+    let mut counts = vec![0u32; grid.columns * grid.rows];
+    for row in rows {
+        let (i, j) = grid.cell_of(row.x, row.y);
+        counts[j * grid.columns + i] += 1;
+    }
+    let max = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+
+    let cell_colors: Vec<[u8; 3]> = counts
+        .iter()
+        .enumerate()
+        .map(|(index, &count)| {
+            let (i, j) = (index % grid.columns, index / grid.columns);
+            if count == 0 {
+                let c = grid.color(theme, i, j);
+                [c.r, c.g, c.b]
+            } else {
+                // 开方让停留较少的格子也能看出差别
+                let t = (count as f64 / max).sqrt();
+                lerp(&theme.ball.gradient_start, &theme.ball.gradient_end, t)
+            }
+        })
+        .collect();
+
+    let (w, h) = (width.round() as u32, height.round() as u32);
+    let mut pixels = Vec::with_capacity(w as usize * h as usize * 3);
+    for y in 0..h {
+        for x in 0..w {
+            let (i, j) = grid.cell_of(x as f64 + 0.5, y as f64 + 0.5);
+            pixels.extend_from_slice(&cell_colors[j * grid.columns + i]);
+        }
+    }
+
+    let mut encoder = png::Encoder::new(out, w, h);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&pixels).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{run, SimOptions};
+    use eyemotion_core::TrainingProgram;
+
+    fn rows() -> Vec<TrajectoryRow> {
+        let options = SimOptions {
+            program: TrainingProgram::default(),
+            seed: 4,
+            width: 1920.0,
+            height: 1080.0,
+            tick_rate: 30.0,
+            fixed_dt: None,
+            duration: Some(10.0),
+        };
+        run(&options).unwrap().trajectory
+    }
+
+    #[test]
+    fn test_svg_uses_theme() {
+        let rows = rows();
+        let active = active_rows(&rows, Some(1));
+        assert!(!active.is_empty() && active.len() < rows.len());
+
+        let theme = Theme::default();
+        let svg = render_svg(&active, &theme, 1920.0, 1080.0);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(&hex(&theme.background.grid_color_dark)));
+        assert!(svg.contains(&hex(&theme.ball.gradient_start)));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_heatmap_png() {
+        let rows = rows();
+        let mut png = Vec::new();
+        render_heatmap_png(&active_rows(&rows, None), &Theme::default(), 320.0, 180.0, &mut png).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+
+        for (width, height) in [(40_000.0, 40_000.0), (0.0, 180.0), (320.0, f64::NAN)] {
+            let err = render_heatmap_png(&[], &Theme::default(), width, height, Vec::new()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_grid_matches_renderer() {
+        let theme = Theme::default();
+        let (dark, light) = (&theme.background.grid_color_dark, &theme.background.grid_color_light);
+
+        // 1920 / 80 整除，左上角为深色
+        let grid = Grid::new(&theme, 1920.0, 1080.0);
+        assert_eq!((grid.columns, grid.rows), (24, 14));
+        assert_eq!(hex(grid.color(&theme, 0, 0)), hex(dark));
+        assert_eq!(hex(grid.color(&theme, 1, 0)), hex(light));
+
+        // 1000 % 80 = 40，偏移半格，Math.round(-0.25) = 0；1080 % 80 = 40 同理
+        let grid = Grid::new(&theme, 1000.0, 1080.0);
+        assert_eq!(hex(grid.color(&theme, 0, 0)), hex(dark));
+        assert_eq!(grid.cell_of(999.0, 1079.0), (grid.columns - 1, grid.rows - 1));
+    }
+}
@@ -1,22 +1,150 @@
-use eyemotion_core::Theme;
-use tauri::{State, Window};
+use eyemotion_core::{
+    AudioParams, ExportOptions, GazeConfig, GazeListener, HistoryTotals, PointerSample, Profile, ProfileList, Profiles, RecordEntry,
+    SessionRecord, SessionRecorder, StageReport, Theme, TrackingReport, TrainingProgram, UserConfig,
+};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+/// 音频设置改变时发给前端的事件，负载为新的 `AudioParams`
+pub const AUDIO_CHANGED_EVENT: &str = "audio-changed";
+
+/// 把一次操作写入本次训练的录制文件；录制失败不影响训练
+fn record(state: &super::state::AppState, entry: RecordEntry) {
+    if let Ok(mut recorder) = state.recorder.lock() {
+        if let Some(recorder) = recorder.as_mut() {
+            if let Err(e) = recorder.record(&entry) {
+                log::warn!("Failed to record session: {}", e);
+            }
+        }
+    }
+}
 
 #[tauri::command]
 pub async fn tick(
     state: State<'_, super::state::AppState>,
     dt: f64,
-) -> Result<(eyemotion_core::GameState, Vec<eyemotion_core::GameEvent>), String> {
+) -> Result<(eyemotion_core::GameState, Vec<eyemotion_core::GameEvent>, Option<TrackingReport>), String> {
     let mut game_state = state.game_state.lock().map_err(|e| e.to_string())?;
+    start_recording(&state, &game_state);
+
+    if let Ok(gaze) = state.gaze.lock() {
+        if let Some(listener) = gaze.as_ref() {
+            let space = state.user_config.lock().map(|c| c.gaze.space).unwrap_or_default();
+            for sample in listener.drain() {
+                game_state.record_gaze(sample, space);
+                record(&state, RecordEntry::Gaze { sample, space });
+            }
+        }
+    }
 
     let update = game_state.update(dt).map_err(|e| e.to_string())?;
 
-    Ok((game_state.clone(), update.events))
+    if let Ok(mut recorder) = state.recorder.lock() {
+        if let Some(recorder) = recorder.as_mut() {
+            if let Err(e) = recorder.record_tick(dt, &game_state, &update.events) {
+                log::warn!("Failed to record session: {}", e);
+            }
+        }
+    }
+    if update.events.contains(&eyemotion_core::GameEvent::GameOver) {
+        finish_recording(&state);
+    }
+
+    // 只记录正在训练的关卡，开始界面上的关卡可能是刚恢复或重置的
+    if !game_state.is_start_screen && !game_state.is_game_over {
+        if let Ok(mut config) = state.user_config.lock() {
+            if config.last_played_stage != game_state.stage {
+                config.last_played_stage = game_state.stage;
+                if let Err(e) = config.save() {
+                    log::warn!("Failed to save config: {}", e);
+                }
+            }
+        }
+    }
+
+    if let Ok(mut history) = state.history.lock() {
+        if let Some(history) = history.as_mut() {
+            if let Err(e) = history.on_tick(&game_state, &update) {
+                log::warn!("Failed to save session history: {}", e);
+            }
+        }
+    }
+
+    Ok((game_state.clone(), update.events, update.tracking))
 }
 
+/// 离开开始界面后开始录制本次训练，与历史记录中的训练同时开始
+fn start_recording(state: &super::state::AppState, game_state: &eyemotion_core::GameState) {
+    if game_state.is_start_screen || game_state.is_game_over {
+        return;
+    }
+    if let Ok(mut recorder) = state.recorder.lock() {
+        if recorder.is_none() {
+            match SessionRecorder::start(game_state) {
+                Ok(started) => *recorder = Some(started),
+                Err(e) => log::error!("Failed to start session recording: {}", e),
+            }
+        }
+    }
+}
+
+/// 写出缓冲中的记录并关闭录制文件
+fn finish_recording(state: &super::state::AppState) {
+    if let Ok(mut recorder) = state.recorder.lock() {
+        if let Some(recorder) = recorder.take() {
+            if let Err(e) = recorder.into_inner() {
+                log::warn!("Failed to record session: {}", e);
+            }
+        }
+    }
+}
+
+/// 结束并保存当前训练的历史记录与录制（重新开始、切换程序或退出时）
+pub fn finish_session(state: &super::state::AppState) {
+    if let Ok(mut history) = state.history.lock() {
+        if let Some(history) = history.as_mut() {
+            if let Err(e) = history.finish() {
+                log::warn!("Failed to save session history: {}", e);
+            }
+        }
+    }
+    finish_recording(state);
+}
+
+/// 前端在每次 tick 之前提交最新的指针采样（逻辑坐标）
 #[tauri::command]
-pub fn reset_game(state: State<'_, super::state::AppState>, w: f64, h: f64) -> eyemotion_core::GameState {
+pub fn submit_pointer_samples(
+    state: State<'_, super::state::AppState>,
+    samples: Vec<PointerSample>,
+) -> Result<(), String> {
+    let mut game_state = state.game_state.lock().map_err(|e| e.to_string())?;
+    for sample in samples {
+        game_state.record_pointer(sample);
+        record(&state, RecordEntry::Pointer { sample });
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_fixed_timestep(state: State<'_, super::state::AppState>, fixed_dt: f64) -> Result<(), String> {
+    let mut game_state = state.game_state.lock().map_err(|e| e.to_string())?;
+    game_state.set_fixed_dt(fixed_dt).map_err(|e| e.to_string())?;
+    record(&state, RecordEntry::SetFixedDt { fixed_dt });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn reset_game(
+    state: State<'_, super::state::AppState>,
+    w: f64,
+    h: f64,
+    seed: Option<u64>,
+) -> eyemotion_core::GameState {
+    finish_session(&state);
     if let Ok(mut game_state) = state.game_state.lock() {
-        game_state.reset(w, h);
+        match seed {
+            Some(seed) => game_state.reset_with_seed(w, h, seed),
+            None => game_state.reset(w, h),
+        }
         game_state.clone()
     } else {
         eyemotion_core::GameState::new(w, h)
@@ -27,6 +155,7 @@ pub fn reset_game(state: State<'_, super::state::AppState>, w: f64, h: f64) -> e
 pub fn resize_game(state: State<'_, super::state::AppState>, w: f64, h: f64) {
     if let Ok(mut game_state) = state.game_state.lock() {
         game_state.resize(w, h);
+        record(&state, RecordEntry::Resize { w, h });
     }
 }
 
@@ -34,6 +163,7 @@ pub fn resize_game(state: State<'_, super::state::AppState>, w: f64, h: f64) {
 pub fn toggle_pause(state: State<'_, super::state::AppState>) {
     if let Ok(mut game_state) = state.game_state.lock() {
         game_state.paused = !game_state.paused;
+        record(&state, RecordEntry::Pause { paused: game_state.paused });
     }
 }
 
@@ -49,6 +179,7 @@ pub fn next_stage(state: State<'_, super::state::AppState>) -> Result<eyemotion_
     if let Ok(mut game_state) = state.game_state.lock() {
         let mut events = Vec::new();
         game_state.next_stage(&mut events);
+        record(&state, RecordEntry::NextStage);
         Ok(game_state.clone())
     } else {
         Err("Failed to lock game state".to_string())
@@ -60,6 +191,7 @@ pub fn prev_stage(state: State<'_, super::state::AppState>) -> Result<eyemotion_
     if let Ok(mut game_state) = state.game_state.lock() {
         let mut events = Vec::new();
         game_state.prev_stage(&mut events);
+        record(&state, RecordEntry::PrevStage);
         Ok(game_state.clone())
     } else {
         Err("Failed to lock game state".to_string())
@@ -67,7 +199,8 @@ pub fn prev_stage(state: State<'_, super::state::AppState>) -> Result<eyemotion_
 }
 
 #[tauri::command]
-pub fn exit_app() {
+pub fn exit_app(state: State<'_, super::state::AppState>) {
+    finish_session(&state);
     std::process::exit(0);
 }
 
@@ -104,11 +237,10 @@ pub fn get_theme() -> Theme {
 }
 
 #[tauri::command]
-pub fn set_language(state: State<'_, super::state::AppState>, language: String) {
-    if let Ok(mut config) = state.user_config.lock() {
-        config.language = language.clone();
-        let _ = config.save();
-    }
+pub fn set_language(state: State<'_, super::state::AppState>, language: String) -> Result<(), String> {
+    let mut config = state.user_config.lock().map_err(|e| e.to_string())?;
+    config.language = language;
+    config.save().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -128,3 +260,242 @@ pub fn get_config(state: State<'_, super::state::AppState>) -> eyemotion_core::U
         eyemotion_core::UserConfig::default()
     }
 }
+
+#[tauri::command]
+pub fn get_audio(state: State<'_, super::state::AppState>) -> AudioParams {
+    state
+        .user_config
+        .lock()
+        .map(|config| config.audio.clone())
+        .unwrap_or_default()
+}
+
+/// 检查并保存音频设置，然后通知前端立即应用
+#[tauri::command]
+pub fn set_audio(app: AppHandle, state: State<'_, super::state::AppState>, audio: AudioParams) -> Result<(), String> {
+    audio.validate().map_err(|e| e.to_string())?;
+    {
+        let mut config = state.user_config.lock().map_err(|e| e.to_string())?;
+        config.audio = audio.clone();
+        config.save().map_err(|e| e.to_string())?;
+    }
+    app.emit(AUDIO_CHANGED_EVENT, audio).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_programs() -> Result<Vec<TrainingProgram>, String> {
+    TrainingProgram::list_available().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn select_program(
+    state: State<'_, super::state::AppState>,
+    name: String,
+) -> Result<eyemotion_core::GameState, String> {
+    let program = TrainingProgram::find(&name).map_err(|e| e.to_string())?;
+    finish_session(&state);
+
+    let mut game_state = state.game_state.lock().map_err(|e| e.to_string())?;
+    game_state.set_program(program).map_err(|e| e.to_string())?;
+
+    if let Ok(mut config) = state.user_config.lock() {
+        config.selected_program = name;
+        config.save().map_err(|e| e.to_string())?;
+    }
+
+    Ok(game_state.clone())
+}
+
+/// 按配置启动眼动仪监听，失败时记录日志并返回 `None`
+pub fn start_gaze_listener(config: &GazeConfig) -> Option<GazeListener> {
+    if !config.enabled {
+        return None;
+    }
+    if let Err(e) = config.validate() {
+        log::error!("Not starting gaze listener: {}", e);
+        return None;
+    }
+    match GazeListener::bind(config.protocol, config.port) {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            log::error!("Failed to start gaze listener on port {}: {}", config.port, e);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_gaze_config(state: State<'_, super::state::AppState>) -> GazeConfig {
+    state
+        .user_config
+        .lock()
+        .map(|config| config.gaze.clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn set_gaze_config(state: State<'_, super::state::AppState>, gaze: GazeConfig) -> Result<(), String> {
+    gaze.validate().map_err(|e| e.to_string())?;
+    let mut listener = state.gaze.lock().map_err(|e| e.to_string())?;
+    // 先释放旧的端口，再按新配置重新监听
+    *listener = None;
+    if gaze.enabled {
+        *listener = Some(GazeListener::bind(gaze.protocol, gaze.port).map_err(|e| e.to_string())?);
+    }
+
+    let mut config = state.user_config.lock().map_err(|e| e.to_string())?;
+    config.gaze = gaze;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// 当前关卡到目前为止的跟踪分析
+#[tauri::command]
+pub fn get_stage_report(state: State<'_, super::state::AppState>) -> Result<StageReport, String> {
+    let game_state = state.game_state.lock().map_err(|e| e.to_string())?;
+    Ok(game_state.stage_report())
+}
+
+#[tauri::command]
+pub fn list_history(state: State<'_, super::state::AppState>) -> Result<Vec<SessionRecord>, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    match history.as_ref() {
+        Some(history) => history.list().map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[tauri::command]
+pub fn delete_history_entry(state: State<'_, super::state::AppState>, id: u64) -> Result<bool, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    match history.as_ref() {
+        Some(history) => history.delete(id).map_err(|e| e.to_string()),
+        None => Ok(false),
+    }
+}
+
+/// 训练时长与连续天数统计；`utc_offset_minutes` 为前端所在时区相对 UTC 的分钟数
+#[tauri::command]
+pub fn get_history_totals(
+    state: State<'_, super::state::AppState>,
+    utc_offset_minutes: i32,
+) -> Result<HistoryTotals, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    match history.as_ref() {
+        Some(history) => history.totals(utc_offset_minutes).map_err(|e| e.to_string()),
+        None => Ok(HistoryTotals::default()),
+    }
+}
+
+/// 导出训练历史，返回写入的文件路径；未指定目录时写到用户的文档目录
+#[tauri::command]
+pub fn export_history(
+    app: AppHandle,
+    state: State<'_, super::state::AppState>,
+    options: ExportOptions,
+    directory: Option<String>,
+) -> Result<Vec<String>, String> {
+    let directory = match directory {
+        Some(directory) => std::path::PathBuf::from(directory),
+        None => app.path().document_dir().map_err(|e| e.to_string())?,
+    };
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    let history = history.as_ref().ok_or("Session history is not available")?;
+    let paths = history.export(&options, &directory).map_err(|e| e.to_string())?;
+    Ok(paths.iter().map(|p| p.display().to_string()).collect())
+}
+
+fn with_profiles<T>(
+    state: &super::state::AppState,
+    f: impl FnOnce(&mut Profiles) -> eyemotion_core::Result<T>,
+) -> Result<T, String> {
+    let mut profiles = state.profiles.lock().map_err(|e| e.to_string())?;
+    let profiles = profiles.as_mut().ok_or("Profiles are not available")?;
+    f(profiles).map_err(|e| e.to_string())
+}
+
+/// 切换到当前档案：保存正在进行的训练，换用该档案的配置、历史、眼动仪设置、训练程序与关卡
+fn activate_profile(state: &super::state::AppState, profiles: &Profiles) -> Result<UserConfig, String> {
+    finish_session(state);
+    let history = profiles.open_history().map_err(|e| e.to_string())?;
+    *state.history.lock().map_err(|e| e.to_string())? = Some(history);
+
+    let config = profiles.load_config();
+    {
+        let mut listener = state.gaze.lock().map_err(|e| e.to_string())?;
+        *listener = None;
+        *listener = start_gaze_listener(&config.gaze);
+    }
+
+    let program = TrainingProgram::find(&config.selected_program).unwrap_or_default();
+    {
+        let mut game_state = state.game_state.lock().map_err(|e| e.to_string())?;
+        if game_state.program != program {
+            game_state.set_program(program).map_err(|e| e.to_string())?;
+        }
+        // 每个档案从自己上次训练到的关卡继续
+        game_state.reset_to_stage(config.last_played_stage);
+    }
+
+    *state.user_config.lock().map_err(|e| e.to_string())? = config.clone();
+    Ok(config)
+}
+
+#[tauri::command]
+pub fn list_profiles(state: State<'_, super::state::AppState>) -> Result<ProfileList, String> {
+    with_profiles(&state, |profiles| Ok(profiles.list().clone()))
+}
+
+#[tauri::command]
+pub fn create_profile(state: State<'_, super::state::AppState>, name: String) -> Result<Profile, String> {
+    with_profiles(&state, |profiles| profiles.create(&name))
+}
+
+#[tauri::command]
+pub fn rename_profile(state: State<'_, super::state::AppState>, id: String, name: String) -> Result<(), String> {
+    with_profiles(&state, |profiles| profiles.rename(&id, &name))
+}
+
+/// 切换档案，返回新档案的配置
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    state: State<'_, super::state::AppState>,
+    id: String,
+) -> Result<UserConfig, String> {
+    let mut profiles = state.profiles.lock().map_err(|e| e.to_string())?;
+    let profiles = profiles.as_mut().ok_or("Profiles are not available")?;
+    profiles.switch(&id).map_err(|e| e.to_string())?;
+    let config = activate_profile(&state, profiles)?;
+    app.emit(AUDIO_CHANGED_EVENT, &config.audio).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// 删除档案，返回当前使用的配置（删除的是当前档案时已切换到其他档案）
+#[tauri::command]
+pub fn delete_profile(
+    app: AppHandle,
+    state: State<'_, super::state::AppState>,
+    id: String,
+) -> Result<UserConfig, String> {
+    let mut profiles = state.profiles.lock().map_err(|e| e.to_string())?;
+    let profiles = profiles.as_mut().ok_or("Profiles are not available")?;
+    let was_active = profiles.active().id == id;
+    if was_active {
+        // 先结束训练，避免把历史写进即将删除的目录
+        finish_session(&state);
+        *state.history.lock().map_err(|e| e.to_string())? = None;
+    }
+    if let Err(e) = profiles.delete(&id) {
+        if was_active {
+            activate_profile(&state, profiles)?;
+        }
+        return Err(e.to_string());
+    }
+    if was_active {
+        let config = activate_profile(&state, profiles)?;
+        app.emit(AUDIO_CHANGED_EVENT, &config.audio).map_err(|e| e.to_string())?;
+        Ok(config)
+    } else {
+        Ok(state.user_config.lock().map_err(|e| e.to_string())?.clone())
+    }
+}
@@ -3,18 +3,17 @@ mod state;
 
 use commands::*;
 use state::AppState;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_log::Builder::default().build())
         .plugin(tauri_plugin_shell::init())
-        .manage(AppState {
-            game_state: std::sync::Mutex::new(eyemotion_core::GameState::new(800.0, 600.0)),
-            user_config: std::sync::Mutex::new(eyemotion_core::UserConfig::load()),
-        })
         .invoke_handler(tauri::generate_handler![
             tick,
+            set_fixed_timestep,
+            submit_pointer_samples,
             reset_game,
             resize_game,
             toggle_pause,
@@ -28,14 +27,71 @@ pub fn run() {
             get_theme,
             set_language,
             get_language,
-            get_config
+            get_config,
+            get_audio,
+            set_audio,
+            list_programs,
+            select_program,
+            get_gaze_config,
+            set_gaze_config,
+            get_stage_report,
+            list_history,
+            delete_history_entry,
+            get_history_totals,
+            export_history,
+            list_profiles,
+            create_profile,
+            rename_profile,
+            delete_profile,
+            switch_profile
         ])
-        .setup(|_app| {
+        .setup(|app| {
             println!("Tauri setup started");
+            // 移动平台没有默认的配置目录，使用 Tauri 提供的应用数据目录
+            #[cfg(mobile)]
+            match app.path().app_data_dir() {
+                Ok(dir) => eyemotion_core::UserConfig::set_config_dir(dir),
+                Err(e) => log::error!("Failed to resolve app data directory: {}", e),
+            }
+
+            let profiles = match eyemotion_core::Profiles::open() {
+                Ok(profiles) => Some(profiles),
+                Err(e) => {
+                    log::error!("Failed to open profiles: {}", e);
+                    None
+                }
+            };
+            let user_config = profiles
+                .as_ref()
+                .map_or_else(eyemotion_core::UserConfig::load, |profiles| profiles.load_config());
+            let program = eyemotion_core::TrainingProgram::find(&user_config.selected_program).unwrap_or_default();
+            let game_state = eyemotion_core::GameState::with_program(800.0, 600.0, program)
+                .unwrap_or_else(|_| eyemotion_core::GameState::new(800.0, 600.0));
+            let gaze = start_gaze_listener(&user_config.gaze);
+            let history = match profiles
+                .as_ref()
+                .map_or_else(eyemotion_core::History::open, |profiles| profiles.open_history())
+            {
+                Ok(history) => Some(history),
+                Err(e) => {
+                    log::error!("Failed to open session history: {}", e);
+                    None
+                }
+            };
+
+            app.manage(AppState {
+                game_state: std::sync::Mutex::new(game_state),
+                user_config: std::sync::Mutex::new(user_config),
+                gaze: std::sync::Mutex::new(gaze),
+                recorder: std::sync::Mutex::new(None),
+                history: std::sync::Mutex::new(history),
+                profiles: std::sync::Mutex::new(profiles),
+            });
             Ok(())
         })
-        .on_window_event(|_window, event| {
+        .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { .. } = event {
+                finish_session(&window.state::<AppState>());
                 std::process::exit(0);
             }
         })
@@ -1,7 +1,15 @@
-use eyemotion_core::{GameState, UserConfig};
+use eyemotion_core::{GameState, GazeListener, History, Profiles, SessionRecorder, UserConfig};
 use std::sync::Mutex;
 
 pub struct AppState {
     pub game_state: Mutex<GameState>,
     pub user_config: Mutex<UserConfig>,
+    /// 眼动仪输入，未启用时为空
+    pub gaze: Mutex<Option<GazeListener>>,
+    /// 正在进行的训练的录制，未在训练或无法创建录制文件时为空
+    pub recorder: Mutex<Option<SessionRecorder>>,
+    /// 训练历史，无法打开历史目录时为空
+    pub history: Mutex<Option<History>>,
+    /// 用户档案，无法读取档案列表时为空（此时使用配置目录中的单一配置）
+    pub profiles: Mutex<Option<Profiles>>,
 }
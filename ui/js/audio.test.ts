@@ -0,0 +1,119 @@
+import { afterEach, beforeEach, describe, it } from 'node:test';
+import assert from 'node:assert/strict';
+import { AudioPlayer, BGM_GAIN } from './audio';
+import { AudioParams, Bridge } from './bridge';
+
+// 只实现 BGM 与音效用到的 Web Audio 接口
+class FakeParam {
+    value = 0;
+    setValueAtTime(): void {}
+    linearRampToValueAtTime(): void {}
+    exponentialRampToValueAtTime(): void {}
+    cancelScheduledValues(): void {}
+}
+
+class FakeNode {
+    type = '';
+    buffer: unknown = null;
+    gain = new FakeParam();
+    frequency = new FakeParam();
+    connect(): void {}
+    start(): void {}
+    stop(): void {}
+}
+
+class FakeAudioContext {
+    currentTime = 0;
+    sampleRate = 8000;
+    state = 'running';
+    destination = new FakeNode();
+    createGain(): FakeNode { return new FakeNode(); }
+    createOscillator(): FakeNode { return new FakeNode(); }
+    createBiquadFilter(): FakeNode { return new FakeNode(); }
+    createBufferSource(): FakeNode { return new FakeNode(); }
+    createBuffer(_channels: number, length: number) {
+        return { getChannelData: () => new Float32Array(length) };
+    }
+    async resume(): Promise<void> {}
+}
+
+function params(enabled: boolean, volume = 0.15): AudioParams {
+    return {
+        bgm: { enabled, volume, chords: [[130.81, 261.63, 329.63]], melody: [523.25, 0] },
+        sfx: { enabled: true, volume: 0.8, bounce_freq_start: 150, bounce_freq_end: 75, bounce_duration: 0.08 },
+    };
+}
+
+describe('AudioPlayer live updates', () => {
+    const setAudio = Bridge.setAudio;
+    let saved: AudioParams[];
+    let audio: AudioPlayer;
+
+    beforeEach(() => {
+        // 不运行 BGM 的调度定时器，只检查播放状态
+        (globalThis as any).window = {
+            AudioContext: FakeAudioContext,
+            setTimeout: () => 0,
+            clearTimeout: () => {},
+        };
+        saved = [];
+        Bridge.setAudio = async (params: AudioParams) => { saved.push(params); };
+        audio = new AudioPlayer();
+        audio.init();
+    });
+
+    afterEach(() => {
+        audio.stopBGM();
+        Bridge.setAudio = setAudio;
+        delete (globalThis as any).window;
+    });
+
+    it('stops music when it is disabled', () => {
+        audio.applyParams(params(true));
+        audio.startBGM();
+        assert.equal(audio.bgmPlaying, true);
+
+        audio.applyParams(params(false));
+        assert.equal(audio.bgmPlaying, false);
+    });
+
+    it('starts music again when it is re-enabled during training', () => {
+        audio.applyParams(params(false));
+        audio.startBGM();
+        assert.equal(audio.bgmPlaying, false);
+
+        audio.applyParams(params(true));
+        assert.equal(audio.bgmPlaying, true);
+    });
+
+    it('does not start music outside training when it is enabled', () => {
+        audio.applyParams(params(false));
+        audio.applyParams(params(true));
+        assert.equal(audio.bgmPlaying, false);
+    });
+
+    it('keeps the previous loudness at the default volume', () => {
+        audio.applyParams(params(true));
+        assert.ok(Math.abs(audio.bgmVolume - 0.5) < 1e-9);
+        audio.applyParams(params(true, 0.3));
+        assert.ok(Math.abs(audio.bgmVolume - 0.3 * BGM_GAIN) < 1e-9);
+    });
+
+    it('saves the music toggle so the next audio-changed keeps it', async () => {
+        audio.applyParams(params(true));
+        audio.startBGM();
+
+        await audio.toggleMusic();
+        assert.equal(audio.bgmPlaying, false);
+        assert.equal(saved.length, 1);
+        assert.equal(saved[0].bgm.enabled, false);
+
+        // 后端保存后广播的设置与本地一致，不会把音乐重新打开
+        audio.applyParams(saved[0]);
+        assert.equal(audio.bgmPlaying, false);
+
+        await audio.toggleMusic();
+        assert.equal(saved[1].bgm.enabled, true);
+        assert.equal(audio.bgmPlaying, true);
+    });
+});
@@ -1,9 +1,25 @@
+import { AudioParams, Bridge, SFXParams } from './bridge';
+
+// BGM 主音量 = BGM_GAIN × 设置中的音量，默认音量 0.15 时为 0.5，与音效的 0.25 × 音量同理
+export const BGM_GAIN = 0.5 / 0.15;
+
 export class AudioPlayer {
     private audioCtx: AudioContext | null = null;
     public bgmEnabled: boolean = true;
     public sfxEnabled: boolean = true;
+    // 游戏是否处于应当播放 BGM 的状态（训练开始后、退出或切到后台前）
+    private bgmWanted: boolean = false;
     private bgmPlayer: BGMPlayer | null = null;
     private initialized: boolean = false;
+    // 后端的音频设置，在初始化之前收到时先保存，初始化后再应用到 BGM
+    private params: AudioParams | null = null;
+    private sfx: SFXParams = {
+        enabled: true,
+        volume: 0.8,
+        bounce_freq_start: 150,
+        bounce_freq_end: 75,
+        bounce_duration: 0.08,
+    };
 
     constructor() {
         this.audioCtx = null;
@@ -23,6 +39,7 @@ export class AudioPlayer {
             this.audioCtx = new AudioContextClass();
             this.bgmPlayer = new BGMPlayer(this.audioCtx);
             this.initialized = true;
+            if (this.params) this.applyParams(this.params);
             console.log('Audio: Initialized');
         } catch (e) {
             console.error('Audio initialization failed:', e);
@@ -41,17 +58,51 @@ export class AudioPlayer {
     }
 
     startBGM(): void {
+        this.bgmWanted = true;
         if (!this.initialized || !this.bgmPlayer) return;
         if (this.bgmEnabled) this.bgmPlayer.start();
     }
 
     stopBGM(): void {
+        this.bgmWanted = false;
         if (this.bgmPlayer) this.bgmPlayer.stop();
     }
 
-    toggleMusic(): void {
-        this.bgmEnabled = !this.bgmEnabled;
-        if (this.bgmPlayer) this.bgmPlayer.toggle(this.bgmEnabled);
+    get bgmPlaying(): boolean {
+        return this.bgmPlayer?.playing ?? false;
+    }
+
+    get bgmVolume(): number {
+        return this.bgmPlayer?.currentVolume ?? 0;
+    }
+
+    /** 开关背景音乐并通过 `set_audio` 保存，之后收到的 `audio-changed` 不会把它改回去 */
+    async toggleMusic(): Promise<void> {
+        const enabled = !this.bgmEnabled;
+        if (!this.params) {
+            this.bgmEnabled = enabled;
+            if (this.bgmPlayer) this.bgmPlayer.toggle(enabled && this.bgmWanted);
+            return;
+        }
+        const params: AudioParams = { ...this.params, bgm: { ...this.params.bgm, enabled } };
+        this.applyParams(params);
+        await Bridge.setAudio(params);
+    }
+
+    /** 应用后端的音频设置，可在播放过程中随时调用 */
+    applyParams(params: AudioParams): void {
+        this.params = params;
+        this.sfx = params.sfx;
+        this.sfxEnabled = params.sfx.enabled;
+
+        const wasEnabled = this.bgmEnabled;
+        this.bgmEnabled = params.bgm.enabled;
+        if (this.bgmPlayer) {
+            this.bgmPlayer.setMusic(params.bgm.chords, params.bgm.melody);
+            this.bgmPlayer.setVolume(BGM_GAIN * params.bgm.volume);
+            if (wasEnabled && !this.bgmEnabled) this.bgmPlayer.stop();
+            if (!wasEnabled && this.bgmEnabled && this.bgmWanted) this.bgmPlayer.start();
+        }
     }
 
     playBounce(): void {
@@ -61,19 +112,21 @@ export class AudioPlayer {
         try {
             const osc = this.audioCtx.createOscillator();
             const gain = this.audioCtx.createGain();
+            const { bounce_freq_start, bounce_freq_end, bounce_duration, volume } = this.sfx;
 
             osc.type = 'sine';
-            osc.frequency.setValueAtTime(150, this.audioCtx.currentTime);
-            osc.frequency.exponentialRampToValueAtTime(75, this.audioCtx.currentTime + 0.08);
+            osc.frequency.setValueAtTime(bounce_freq_start, this.audioCtx.currentTime);
+            osc.frequency.exponentialRampToValueAtTime(bounce_freq_end, this.audioCtx.currentTime + bounce_duration);
 
-            gain.gain.setValueAtTime(0.2, this.audioCtx.currentTime);
-            gain.gain.exponentialRampToValueAtTime(0.01, this.audioCtx.currentTime + 0.08);
+            // 默认音量 0.8 时峰值为 0.2
+            gain.gain.setValueAtTime(Math.max(0.25 * volume, 0.0001), this.audioCtx.currentTime);
+            gain.gain.exponentialRampToValueAtTime(0.0001, this.audioCtx.currentTime + bounce_duration);
 
             osc.connect(gain);
             gain.connect(this.audioCtx.destination);
 
             osc.start();
-            osc.stop(this.audioCtx.currentTime + 0.08);
+            osc.stop(this.audioCtx.currentTime + bounce_duration);
         } catch (e) {
             console.error('Audio: Failed to play bounce sound:', e);
         }
@@ -92,8 +145,8 @@ export class AudioPlayer {
             osc.frequency.setValueAtTime(800, this.audioCtx.currentTime);
             osc.frequency.exponentialRampToValueAtTime(400, this.audioCtx.currentTime + 0.05);
 
-            gain.gain.setValueAtTime(0.15, this.audioCtx.currentTime);
-            gain.gain.exponentialRampToValueAtTime(0.01, this.audioCtx.currentTime + 0.05);
+            gain.gain.setValueAtTime(Math.max(0.19 * this.sfx.volume, 0.0001), this.audioCtx.currentTime);
+            gain.gain.exponentialRampToValueAtTime(0.0001, this.audioCtx.currentTime + 0.05);
 
             osc.connect(gain);
             gain.connect(this.audioCtx.destination);
@@ -115,6 +168,8 @@ class BGMPlayer {
     private isPlaying: boolean = false;
     private mainGain: GainNode;
     private loopTimeout: number | null = null;
+    private volume: number = 0.5;
+    // 收到后端的音频设置之前使用的默认曲目
     private chords = [
         [130.81, 261.63, 329.63, 392.00], // Cmaj7
         [146.83, 293.66, 349.23, 440.00], // Dm7
@@ -140,7 +195,7 @@ class BGMPlayer {
         this.audioCtx = audioCtx;
         this.mainGain = audioCtx.createGain();
         this.mainGain.connect(audioCtx.destination);
-        this.mainGain.gain.value = 0.5;
+        this.mainGain.gain.value = this.volume;
     }
 
     start(): void {
@@ -164,14 +219,34 @@ class BGMPlayer {
     }
 
     updateVolume(): void {
-        const targetVol = this.isPlaying ? 0.5 : 0;
+        const targetVol = this.isPlaying ? this.volume : 0;
         const now = this.audioCtx.currentTime;
         this.mainGain.gain.cancelScheduledValues(now);
         this.mainGain.gain.linearRampToValueAtTime(targetVol, now + 0.1);
     }
 
     setVolume(volume: number): void {
-        this.mainGain.gain.value = volume;
+        this.volume = volume;
+        if (this.isPlaying) this.updateVolume();
+    }
+
+    get playing(): boolean {
+        return this.isPlaying;
+    }
+
+    get currentVolume(): number {
+        return this.volume;
+    }
+
+    /** 替换和弦与旋律（旋律中的 0 为休止），从下一拍开始生效 */
+    setMusic(chords: number[][], melody: number[]): void {
+        if (chords.length > 0) this.chords = chords;
+        this.melody = melody;
+    }
+
+    // 和弦音数不固定，按位置循环取音
+    private chordNote(chord: number[], index: number): number {
+        return chord[index % chord.length];
     }
 
     private playLoop(): void {
@@ -209,15 +284,14 @@ class BGMPlayer {
 
             // 3. 和声伴奏 (Harmony Section)
             // 电吉他 (Electric Guitar) - 移至中低声部，增加厚度
-            const guitarFreq = currentChord[beat % 4]; // 降低一个八度
+            const guitarFreq = this.chordNote(currentChord, beat % 4); // 降低一个八度
             this.createInstrumentNote(guitarFreq, 0.04, now, beatDuration * 2.0, 'square', {
                 attack: 0.1, decay: 0.5, sustain: 0.4, release: 1.0, filterFreq: 800
             });
 
             // 4. 主奏声部 (Main Section)
             // 钢琴 (Piano) - 增加 Sustain 使音符连贯
-            const melIdx = beat % this.melody.length;
-            const pianoFreq = this.melody[melIdx];
+            const pianoFreq = this.melody.length > 0 ? this.melody[beat % this.melody.length] : 0;
             if (pianoFreq > 0) {
                 this.createInstrumentNote(pianoFreq, 0.12, now, beatDuration * 1.5, 'triangle', {
                     attack: 0.01, decay: 0.3, sustain: 0.4, release: 0.8, filterFreq: 2500
@@ -228,14 +302,14 @@ class BGMPlayer {
             // 彻底重构音色：移除锯齿波，使用更清澈的合成音色，消除“阴间”感
             // 旋律线 A - 水晶三角波 (Crystal Triangle)
             if (beat % 4 === 0) {
-                const v1Freq = currentChord[2] * 2;
+                const v1Freq = this.chordNote(currentChord, 2) * 2;
                 this.createInstrumentNote(v1Freq, 0.08, now, beatDuration * 6.0, 'triangle', {
                     attack: 0.2, decay: 0.3, sustain: 0.7, release: 2.0, vibrato: false, filterFreq: 3000 // 高截止频率，保持清澈
                 });
             }
             // 旋律线 B - 柔和方波 (Soft Square)
             if ((beat + 2) % 8 === 0) {
-                const v2Freq = currentChord[1] * 2;
+                const v2Freq = this.chordNote(currentChord, 1) * 2;
                 this.createInstrumentNote(v2Freq, 0.05, now, beatDuration * 7.0, 'square', {
                     attack: 0.3, decay: 0.4, sustain: 0.6, release: 2.5, vibrato: false, filterFreq: 1500 // 过滤掉高频，使其圆润
                 });
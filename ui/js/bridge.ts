@@ -7,14 +7,209 @@ export interface Ball {
     screen_w: number;
     screen_h: number;
     radius: number;
+    scale: number;
     x: number;
     y: number;
     vx: number;
     vy: number;
 }
 
+export type Axis = 'Horizontal' | 'Vertical' | 'Diagonal';
+
+export type PhasePoint = { Fraction: number } | { Seconds: number };
+
+export type JumpRule =
+    | 'Random'
+    | 'Alternating'
+    | { Grid: { columns: number, rows: number } }
+    | 'AntiSaccade';
+
+export type MotionType =
+    | { Linear: { primary_axis: Axis, bounce_jitter: number } }
+    | { CenterLines: { vertical_speed_ratio: number, switch_at: PhasePoint } }
+    | { Circular: { angular_speed: number, reverse_at: PhasePoint } }
+    | { Spiral: { angular_speed: number, radial_speed: number } }
+    | { Lissajous: { freq_x: number, freq_y: number, phase: number, amplitude: number } }
+    | { Saccade: { dwell: number, rule: JumpRule, amplitude: number } };
+
+export type DepthCurve =
+    | { Sine: { min_scale: number, max_scale: number, period: number } }
+    | { Ramp: { from: number, to: number } };
+
+export interface StageConfig {
+    speed: number;
+    angle_range: [number, number];
+    motion_type: MotionType;
+    duration: number;
+    depth: DepthCurve | null;
+    distractors: DistractorMotion[];
+    ball_collisions: boolean;
+}
+
+export interface TrainingProgram {
+    name: string;
+    transition_duration: number;
+    stages: StageConfig[];
+}
+
+export interface Interpolation {
+    prev_x: number;
+    prev_y: number;
+    alpha: number;
+}
+
+export type DistractorMotion =
+    | { Linear: { speed: number, angle_range: [number, number] } }
+    | { Circular: { angular_speed: number, radius_ratio: number } }
+    | { Lissajous: { freq_x: number, freq_y: number, phase: number, amplitude: number } };
+
+export interface Distractor {
+    ball: Ball;
+    motion: DistractorMotion;
+    prev_x: number;
+    prev_y: number;
+}
+
+export interface PointerSample {
+    x: number;
+    y: number;
+}
+
+export interface TrackingReport {
+    samples: number;
+    last_distance: number;
+    mean_distance: number;
+    time_on_target: number;
+    tracked_time: number;
+    lag: number | null;
+    score: number;
+}
+
+export interface Saccade {
+    start: number;
+    end: number;
+    amplitude: number;
+    peak_speed: number;
+    catch_up: boolean;
+}
+
+export interface Dropout {
+    start: number;
+    end: number;
+    kind: 'Blink' | 'SignalLoss';
+}
+
+export interface PursuitReport {
+    samples: number;
+    gain: number | null;
+    catch_up_saccades: number;
+    saccades: Saccade[];
+    blinks: number;
+    dropouts: Dropout[];
+    dropout_time: number;
+    rms_error: number;
+}
+
+export interface StageReport {
+    stage: number;
+    duration: number;
+    pointer: PursuitReport | null;
+    gaze: PursuitReport | null;
+}
+
+export type GazeSpace =
+    | { type: 'Normalized' }
+    | { type: 'Pixels', width: number, height: number };
+
+export interface GazeConfig {
+    enabled: boolean;
+    protocol: 'Udp' | 'Tcp';
+    port: number;
+    space: GazeSpace;
+}
+
+export interface StageResult {
+    stage: number;
+    score: number | null;
+}
+
+export interface SessionRecord {
+    id: number;
+    started: number;
+    ended: number;
+    program: string;
+    stages_completed: StageResult[];
+    duration: number;
+    pauses: number;
+    finished: boolean;
+}
+
+export interface DailyTotal {
+    date: string;
+    minutes: number;
+    sessions: number;
+}
+
+export interface HistoryTotals {
+    sessions: number;
+    total_minutes: number;
+    today_minutes: number;
+    daily: DailyTotal[];
+    streak_days: number;
+    longest_streak: number;
+}
+
+export interface ExportOptions {
+    format: 'Csv' | 'Json';
+    from?: string | null;
+    to?: string | null;
+    utc_offset_minutes: number;
+}
+
+export interface Profile {
+    id: string;
+    name: string;
+    created: number;
+}
+
+export interface ProfileList {
+    active: string;
+    profiles: Profile[];
+}
+
+export interface BGMParams {
+    enabled: boolean;
+    volume: number;
+    chords: number[][];
+    melody: number[];
+}
+
+export interface SFXParams {
+    enabled: boolean;
+    volume: number;
+    bounce_freq_start: number;
+    bounce_freq_end: number;
+    bounce_duration: number;
+}
+
+export interface AudioParams {
+    bgm: BGMParams;
+    sfx: SFXParams;
+}
+
+export interface UserConfig {
+    version: number;
+    audio: AudioParams;
+    language: string;
+    last_played_stage: number;
+    selected_program: string;
+    gaze: GazeConfig;
+}
+
 export interface GameState {
     ball: Ball;
+    distractors: Distractor[];
+    program: TrainingProgram;
     stage: number;
     stage_elapsed: number;
     paused: boolean;
@@ -22,8 +217,15 @@ export interface GameState {
     transition_timer: number;
     is_game_over: boolean;
     is_start_screen: boolean;
-    stage5_paused: boolean;
-    stage5_pause_elapsed: number;
+    orbit_paused: boolean;
+    orbit_pause_elapsed: number;
+    saccade_timer: number;
+    saccade_jumps: number;
+    seed: number;
+    fixed_dt: number;
+    max_catch_up_steps: number;
+    accumulator: number;
+    interpolation: Interpolation;
 }
 
 export interface Color {
@@ -32,18 +234,21 @@ export interface Color {
     b: number;
 }
 
+export interface BallStyle {
+    gradient_start: Color;
+    gradient_end: Color;
+    outline_color: Color;
+    radius_ratio: number;
+}
+
 export interface Theme {
     background: {
         grid_color_dark: Color;
         grid_color_light: Color;
         grid_size: number;
     };
-    ball: {
-        gradient_start: Color;
-        gradient_end: Color;
-        outline_color: Color;
-        radius_ratio: number;
-    };
+    ball: BallStyle;
+    distractor: BallStyle;
     ui: {
         title_color: Color;
         subtitle_color: Color;
@@ -54,10 +259,16 @@ export interface Theme {
     };
 }
 
+export type Wall =
+    | 'Left' | 'Right' | 'Top' | 'Bottom'
+    | 'TopLeft' | 'TopRight' | 'BottomLeft' | 'BottomRight';
+
 export type GameEvent = 
-    | { type: 'BallBounced' }
+    | { type: 'BallBounced', ball: number, x: number, y: number, wall: Wall, stage_time: number }
+    | { type: 'BallsCollided', a: number, b: number, x: number, y: number, stage_time: number }
+    | { type: 'TargetJumped', from: [number, number], to: [number, number], response: [number, number], stage_time: number }
     | { type: 'StageChanged', from: number, to: number }
-    | { type: 'StageCompleted', stage: number }
+    | { type: 'StageCompleted', stage: number, tracking: TrackingReport | null, report: StageReport }
     | { type: 'GameOver' };
 
 declare global {
@@ -65,7 +276,10 @@ declare global {
         __TAURI__: {
             core: {
                 invoke: (command: string, args?: any) => Promise<any>;
-            }
+            };
+            event: {
+                listen: (event: string, handler: (event: { payload: any }) => void) => Promise<() => void>;
+            };
         }
     }
 }
@@ -86,8 +300,24 @@ export class Bridge {
         }
     }
 
-    static async tick(dt: number): Promise<[GameState, GameEvent[]] | null> {
-        return await this.invoke<[GameState, GameEvent[]]>('tick', { dt });
+    static async tick(dt: number): Promise<[GameState, GameEvent[], TrackingReport | null] | null> {
+        return await this.invoke<[GameState, GameEvent[], TrackingReport | null]>('tick', { dt });
+    }
+
+    static async submitPointerSamples(samples: PointerSample[]): Promise<void> {
+        await this.invoke('submit_pointer_samples', { samples });
+    }
+
+    static async getGazeConfig(): Promise<GazeConfig | null> {
+        return await this.invoke<GazeConfig>('get_gaze_config');
+    }
+
+    static async setGazeConfig(gaze: GazeConfig): Promise<void> {
+        await this.invoke('set_gaze_config', { gaze });
+    }
+
+    static async getStageReport(): Promise<StageReport | null> {
+        return await this.invoke<StageReport>('get_stage_report');
     }
 
     static async showMainWindow(): Promise<void> {
@@ -126,8 +356,8 @@ export class Bridge {
         await this.invoke('start_game');
     }
 
-    static async resetGame(w: number, h: number): Promise<GameState | null> {
-        return await this.invoke<GameState>('reset_game', { w, h });
+    static async resetGame(w: number, h: number, seed?: number): Promise<GameState | null> {
+        return await this.invoke<GameState>('reset_game', { w, h, seed });
     }
 
     static async exitApp(): Promise<void> {
@@ -145,4 +375,77 @@ export class Bridge {
     static async setLanguage(language: string): Promise<void> {
         await this.invoke('set_language', { language });
     }
+
+    static async listPrograms(): Promise<TrainingProgram[] | null> {
+        return await this.invoke<TrainingProgram[]>('list_programs');
+    }
+
+    static async selectProgram(name: string): Promise<GameState | null> {
+        return await this.invoke<GameState>('select_program', { name });
+    }
+
+    static async setFixedTimestep(fixedDt: number): Promise<void> {
+        await this.invoke('set_fixed_timestep', { fixedDt });
+    }
+
+    static async listHistory(): Promise<SessionRecord[] | null> {
+        return await this.invoke<SessionRecord[]>('list_history');
+    }
+
+    static async deleteHistoryEntry(id: number): Promise<boolean | null> {
+        return await this.invoke<boolean>('delete_history_entry', { id });
+    }
+
+    static async getHistoryTotals(): Promise<HistoryTotals | null> {
+        // getTimezoneOffset 是 UTC 减本地时间，后端需要的是本地相对 UTC 的偏移
+        const utcOffsetMinutes = -new Date().getTimezoneOffset();
+        return await this.invoke<HistoryTotals>('get_history_totals', { utcOffsetMinutes });
+    }
+
+    /** 导出训练历史（日期为本地 YYYY-MM-DD，包含首尾两天），返回写入的文件路径 */
+    static async exportHistory(format: 'Csv' | 'Json', from?: string, to?: string, directory?: string): Promise<string[] | null> {
+        const options: ExportOptions = {
+            format,
+            from: from ?? null,
+            to: to ?? null,
+            utc_offset_minutes: -new Date().getTimezoneOffset(),
+        };
+        return await this.invoke<string[]>('export_history', { options, directory });
+    }
+
+    static async listProfiles(): Promise<ProfileList | null> {
+        return await this.invoke<ProfileList>('list_profiles');
+    }
+
+    static async createProfile(name: string): Promise<Profile | null> {
+        return await this.invoke<Profile>('create_profile', { name });
+    }
+
+    static async renameProfile(id: string, name: string): Promise<void> {
+        await this.invoke('rename_profile', { id, name });
+    }
+
+    /** 切换档案，返回新档案的配置 */
+    static async switchProfile(id: string): Promise<UserConfig | null> {
+        return await this.invoke<UserConfig>('switch_profile', { id });
+    }
+
+    /** 删除档案，返回当前使用的配置 */
+    static async deleteProfile(id: string): Promise<UserConfig | null> {
+        return await this.invoke<UserConfig>('delete_profile', { id });
+    }
+
+    static async getAudio(): Promise<AudioParams | null> {
+        return await this.invoke<AudioParams>('get_audio');
+    }
+
+    static async setAudio(audio: AudioParams): Promise<void> {
+        await this.invoke('set_audio', { audio });
+    }
+
+    /** 音频设置改变（包括切换档案）时调用 `handler` */
+    static async onAudioChanged(handler: (audio: AudioParams) => void): Promise<void> {
+        if (!window.__TAURI__) return;
+        await window.__TAURI__.event.listen('audio-changed', (event) => handler(event.payload as AudioParams));
+    }
 }
@@ -12,6 +12,7 @@ export class Game {
     private gameState: GameState | null = null;
     private mousePos: { x: number, y: number } = { x: 0, y: 0 };
     private isMouseDown: boolean = false;
+    private hasPointer: boolean = false;
     private lastMouseMoveTime: number = Date.now();
     private lastTime: number = 0;
     private isProcessing: boolean = false;
@@ -53,6 +54,14 @@ export class Game {
         
         this.renderer.loadTheme();
         this.audio.init();
+
+        try {
+            const audio = await Bridge.getAudio();
+            if (audio) this.audio.applyParams(audio);
+            await Bridge.onAudioChanged((params) => this.audio.applyParams(params));
+        } catch (e) {
+            console.warn('Game: Failed to load audio settings:', e);
+        }
         
         try {
             const savedLang = await Bridge.getLanguage();
@@ -90,6 +99,7 @@ export class Game {
             const dy = pos.y - this.mousePos.y;
             if (Math.abs(dx) > 2 || Math.abs(dy) > 2) {
                 this.mousePos = pos;
+                this.hasPointer = true;
                 this.lastMouseMoveTime = Date.now();
             }
         });
@@ -168,9 +178,15 @@ export class Game {
         this.isProcessing = true;
         
         try {
+            // 每帧提交一次当前指针位置，用于跟踪精度评分
+            if (this.hasPointer) {
+                const sample = this.renderer.toLogical(this.mousePos.x, this.mousePos.y);
+                await Bridge.submitPointerSamples([sample]);
+            }
+
             const result = await Bridge.tick(clampedDt);
             if (result) {
-                const [state, events] = result;
+                const [state, events, tracking] = result;
                 this.gameState = state;
                 
                 if (this.firstTick && state) {
@@ -186,6 +202,9 @@ export class Game {
                 document.dispatchEvent(new CustomEvent('game-state-changed', { 
                     detail: this.gameState 
                 }));
+                document.dispatchEvent(new CustomEvent('tracking-updated', {
+                    detail: tracking
+                }));
             }
         } catch (e) {
             console.error("Game loop error:", e);
@@ -208,7 +227,19 @@ export class Game {
     
     private handleEvent(event: GameEvent): void {
         if (event.type === 'BallBounced') {
-            this.audio.playBounce();
+            // 只为目标小球播放反弹音效，干扰球保持安静
+            if (event.ball === 0) this.audio.playBounce();
+        } else if (event.type === 'TargetJumped') {
+            this.audio.playClick();
+        } else if (event.type === 'StageCompleted') {
+            if (event.tracking) {
+                console.log(`Game: Stage ${event.stage} tracking score ${event.tracking.score.toFixed(1)}`);
+            }
+            const gaze = event.report.gaze;
+            if (gaze) {
+                console.log(`Game: Stage ${event.stage} pursuit gain ${gaze.gain?.toFixed(2) ?? '-'}, ` +
+                    `${gaze.catch_up_saccades} catch-up saccades, ${gaze.blinks} blinks, RMS ${gaze.rms_error.toFixed(1)}`);
+            }
         }
     }
     
@@ -1,4 +1,4 @@
-import { Bridge, GameState, Theme, Color } from './bridge';
+import { Bridge, GameState, Theme, Color, BallStyle } from './bridge';
 
 export class Renderer {
     private canvas: HTMLCanvasElement;
@@ -58,6 +58,12 @@ export class Renderer {
                 outline_color: { r: 255, g: 255, b: 255 },
                 radius_ratio: 0.05
             },
+            distractor: {
+                gradient_start: { r: 148, g: 163, b: 184 }, // #94A3B8
+                gradient_end: { r: 100, g: 116, b: 139 }, // #64748B
+                outline_color: { r: 203, g: 213, b: 225 },
+                radius_ratio: 0.05
+            },
             ui: {
                 title_color: { r: 235, g: 191, b: 66 },
                 subtitle_color: { r: 255, g: 255, b: 255 },
@@ -127,6 +133,18 @@ export class Renderer {
         };
     }
 
+    // 将实际屏幕坐标转换回设计稿坐标（scalePos 的逆变换）
+    public toLogical(x: number, y: number): { x: number, y: number } {
+        const scale = this.getScale();
+        const offsetX = (window.innerWidth - this.logicalWidth * scale) / 2;
+        const offsetY = (window.innerHeight - this.logicalHeight * scale) / 2;
+
+        return {
+            x: (x - offsetX) / scale,
+            y: (y - offsetY) / scale
+        };
+    }
+
     // 将设计稿大小转换为实际屏幕大小
     private scaleSize(size: number): number {
         return size * this.getScale();
@@ -316,12 +334,25 @@ export class Renderer {
         } else {
             // 倒计时期间（is_transitioning 为 true）隐藏小球
             if (!gameState.is_transitioning) {
-                const ballPos = this.scalePos(gameState.ball.x, gameState.ball.y);
+                // 干扰球先绘制，保证目标小球始终在最上层
+                const { alpha: t } = gameState.interpolation;
+                for (const distractor of gameState.distractors) {
+                    const dx = distractor.prev_x + (distractor.ball.x - distractor.prev_x) * t;
+                    const dy = distractor.prev_y + (distractor.ball.y - distractor.prev_y) * t;
+                    const pos = this.scalePos(dx, dy);
+                    this.drawBallAt(pos.x, pos.y, this.scaleSize(distractor.ball.radius), this.theme.distractor);
+                }
+
+                // 在上一物理步与当前物理步之间插值，避免固定步长带来的抖动
+                const { prev_x, prev_y, alpha } = gameState.interpolation;
+                const x = prev_x + (gameState.ball.x - prev_x) * alpha;
+                const y = prev_y + (gameState.ball.y - prev_y) * alpha;
+                const ballPos = this.scalePos(x, y);
                 const ballRadius = this.scaleSize(gameState.ball.radius);
                 this.drawBallAt(ballPos.x, ballPos.y, ballRadius);
             }
             
-            if (gameState.stage === 5 && gameState.stage5_paused) {
+            if (gameState.orbit_paused) {
                 this.drawStage5Pause();
             }
             
@@ -379,18 +410,18 @@ export class Renderer {
         }
     }
 
-    private drawBallAt(x: number, y: number, radius: number): void {
+    private drawBallAt(x: number, y: number, radius: number, style: BallStyle = this.theme.ball): void {
         this.ctx.save();
         const gradient = this.ctx.createLinearGradient(x - radius, y - radius, x + radius, y + radius);
-        gradient.addColorStop(0, this.colorToCSS(this.theme.ball.gradient_start));
-        gradient.addColorStop(1, this.colorToCSS(this.theme.ball.gradient_end));
+        gradient.addColorStop(0, this.colorToCSS(style.gradient_start));
+        gradient.addColorStop(1, this.colorToCSS(style.gradient_end));
         
         this.ctx.beginPath();
         this.ctx.arc(x, y, radius, 0, Math.PI * 2);
         this.ctx.fillStyle = gradient;
         this.ctx.fill();
         
-        this.ctx.strokeStyle = this.colorToCSS(this.theme.ball.outline_color);
+        this.ctx.strokeStyle = this.colorToCSS(style.outline_color);
         this.ctx.lineWidth = 2;
         this.ctx.stroke();
         this.ctx.restore();
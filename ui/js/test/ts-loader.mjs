@@ -0,0 +1,26 @@
+// 测试时用项目已有的 TypeScript 把 .ts 模块转译为 JS，不需要额外的测试框架
+import { readFile } from 'node:fs/promises';
+import { fileURLToPath } from 'node:url';
+import ts from 'typescript';
+
+export async function resolve(specifier, context, nextResolve) {
+    try {
+        return await nextResolve(specifier, context);
+    } catch (e) {
+        // 源码中的相对导入不带扩展名，由 Vite 解析
+        if (specifier.startsWith('.') && !specifier.endsWith('.ts')) {
+            return nextResolve(`${specifier}.ts`, context);
+        }
+        throw e;
+    }
+}
+
+export async function load(url, context, nextLoad) {
+    if (!url.endsWith('.ts')) return nextLoad(url, context);
+    const fileName = fileURLToPath(url);
+    const { outputText } = ts.transpileModule(await readFile(fileName, 'utf8'), {
+        fileName,
+        compilerOptions: { module: ts.ModuleKind.ESNext, target: ts.ScriptTarget.ES2022 },
+    });
+    return { format: 'module', source: outputText, shortCircuit: true };
+}